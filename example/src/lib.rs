@@ -8,8 +8,8 @@ extern crate serde_json;
 extern crate tarantool_rust_api;
 
 use regex::Regex;
-use serde::ser::{Serialize, SerializeMap, Serializer};
 use serde_json::Value;
+use std::cmp::Ordering;
 use std::ffi::CStr;
 use std::io;
 use std::os::raw::{c_char, c_int};
@@ -27,12 +27,15 @@ pub fn test_ffi(v: c_int, ptr: *const c_char) -> c_int {
     v + 1
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(remote = "Self")]
 pub struct TestStruct {
     pub a: u64,
     pub b: Value,
 }
 
+tarantool_struct_as_map!(TestStruct);
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct TestStructSeq {
     pub a: u64,
@@ -40,15 +43,6 @@ pub struct TestStructSeq {
     pub c: TestStruct,
 }
 
-impl Serialize for TestStruct {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
-        let mut map = serializer.serialize_map(Some(2))?;
-        map.serialize_entry("a", &self.a)?;
-        map.serialize_entry("b", &self.b)?;
-        map.end()
-    }
-}
-
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct RowTypeStruct {
     pub id: u32,
@@ -112,14 +106,13 @@ fn test_iterator_impl(tarantool: &TarantoolContext) -> io::Result<Option<Vec<Row
     let re = Regex::new(&search_name_regexp).unwrap();
     let mut result: Vec<RowTypeStruct> = Vec::new();
 
-    for raw_row in tarantool.index_iterator(TEST_SPACE, PRIMARY_INDEX, IteratorType::GE, &(start_key, ))? {
+    for raw_row in tarantool.index_iterator(TEST_SPACE, PRIMARY_INDEX, IteratorType::GE, &(start_key, ))?.with_yield_policy(YieldPolicy::EveryRows(1))? {
         let row = raw_row?;
         let name: String = row.decode_field(1)?;
         if re.is_match(&name) {
             let row: RowTypeStruct = row.decode()?;
             result.push(row);
         }
-        tarantool.fiber_yield();
     };
 
     match result.len() {
@@ -128,6 +121,389 @@ fn test_iterator_impl(tarantool: &TarantoolContext) -> io::Result<Option<Vec<Row
     }
 }
 
+static GROUPED_INDEX: &str = "grouped";
+
+/// scan non-unique index yielding every 2 rows, index is created in the middle of scan to change schema
+fn test_yield_scan_impl(tarantool: &TarantoolContext) -> io::Result<Vec<u32>> {
+    let (iterator, key, ddl_after_rows): (String, Vec<String>, usize) = tarantool.decode_input_params()?;
+    let iterator_type = match iterator.as_str() {
+        "ALL" => IteratorType::ALL,
+        "EQ" => IteratorType::EQ,
+        "REQ" => IteratorType::REQ,
+        "LE" => IteratorType::LE,
+        _ => return Err(io::Error::new(io::ErrorKind::Other, format!("unexpected iterator {}", iterator))),
+    };
+    let mut ids = Vec::new();
+    for row in tarantool.index_iterator(TEST_SPACE, GROUPED_INDEX, iterator_type, &key)?.with_yield_policy(YieldPolicy::EveryRows(2))? {
+        ids.push(row?.decode_field(0)?);
+        if ids.len() == ddl_after_rows {
+            tarantool.create_index(TEST_SPACE, "yield_ddl", &IndexOptions::new(IndexType::Tree, vec![IndexPart::new(0, "unsigned")]))?;
+        }
+    }
+    Ok(ids)
+}
+
+fn test_iterator_typed_impl(tarantool: &TarantoolContext) -> io::Result<(Vec<RowTypeStruct>, Vec<(u32, String)>)> {
+    let (start_key, search_name_regexp): (u32, String) = tarantool.decode_input_params()?;
+    let re = Regex::new(&search_name_regexp).unwrap();
+
+    let rows = tarantool.index_iterator_typed::<RowTypeStruct, _, _, _>(TEST_SPACE, PRIMARY_INDEX, IteratorType::GE, &(start_key, ))?
+        .collect::<io::Result<Vec<_>>>()?;
+    let names = tarantool.index_iterator(TEST_SPACE, PRIMARY_INDEX, IteratorType::GE, &(start_key, ))?
+        .filter_field(1, |name: &String| re.is_match(name))
+        .decode_fields(&[0, 1])
+        .collect::<io::Result<Vec<_>>>()?;
+
+    Ok((rows, names))
+}
+
+fn test_select_impl(tarantool: &TarantoolContext) -> io::Result<(Vec<RowTypeStruct>, Vec<RowTypeStruct>)> {
+    let (lower, upper, offset, limit): (u32, u32, usize, usize) = tarantool.decode_input_params()?;
+    let forward = tarantool.select(TEST_SPACE, PRIMARY_INDEX)
+        .ge(&(lower, ))
+        .lt(&(upper, ))
+        .offset(offset)
+        .limit(limit)
+        .to_vec()?;
+    let backward = tarantool.select(TEST_SPACE, PRIMARY_INDEX)
+        .ge(&(lower, ))
+        .lt(&(upper, ))
+        .reverse()
+        .to_vec()?;
+    Ok((forward, backward))
+}
+
+fn test_scan_page_impl(tarantool: &TarantoolContext) -> io::Result<Page<RowTypeStruct>> {
+    let (index, token, limit): (String, Option<String>, usize) = tarantool.decode_input_params()?;
+    tarantool.scan_page(TEST_SPACE, index, IteratorType::ALL, &Vec::<u32>::new(), token.as_ref().map(|v| v.as_str()), limit)
+}
+
+static GEO_SPACE: &str = "geo_space";
+static GEO_INDEX: &str = "spatial";
+static FLAGS_INDEX: &str = "flags";
+
+fn test_spatial_impl(tarantool: &TarantoolContext) -> io::Result<(Vec<u32>, Vec<u32>, Vec<u32>)> {
+    let (rect, point, mask): (Rect<2>, Point<2>, u64) = tarantool.decode_input_params()?;
+    let within = tarantool.rtree_iterator(GEO_SPACE, GEO_INDEX, &SpatialQuery::Within(rect))?
+        .decode_fields::<(u32, )>(&[0])
+        .map(|row| row.map(|(id, )| id))
+        .collect::<io::Result<Vec<_>>>()?;
+    let nearest = tarantool.rtree_iterator(GEO_SPACE, GEO_INDEX, &SpatialQuery::Neighbor(point))?
+        .take(2)
+        .map(|row| row.and_then(|row| row.decode_field(0)))
+        .collect::<io::Result<Vec<_>>>()?;
+    let flagged = tarantool.bitset_iterator(GEO_SPACE, FLAGS_INDEX, &BitsetQuery::AllSet(mask))?
+        .map(|row| row.and_then(|row| row.decode_field(0)))
+        .collect::<io::Result<Vec<_>>>()?;
+    Ok((within, nearest, flagged))
+}
+
+fn test_schema_info_impl(tarantool: &TarantoolContext) -> io::Result<(String, Vec<String>, Vec<(String, bool, Vec<u32>)>, Option<String>)> {
+    let space = tarantool.space_info(TEST_SPACE)?;
+    let indexes = tarantool.space_indexes(TEST_SPACE)?;
+    let format = space.format.iter().map(|field| field.name.clone()).collect();
+    let indexes = indexes.iter().map(|index| (index.name.clone(), index.unique, index.key_fields())).collect();
+    let check_error = space.check_struct::<RowTypeStruct>().err().map(|e| e.to_string());
+    Ok((space.engine, format, indexes, check_error))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NamedRow {
+    pub name: String,
+    pub id: u32,
+}
+
+fn test_by_names_impl(tarantool: &TarantoolContext) -> io::Result<(Option<String>, Option<NamedRow>)> {
+    let (id, new_name): (u32, String) = tarantool.decode_input_params()?;
+    tarantool.replace_by_names(TEST_SPACE, &NamedRow { name: new_name, id })?;
+    match tarantool.index_get(TEST_SPACE, PRIMARY_INDEX, &(id, ))? {
+        Some(row) => Ok((row.get("name")?, Some(row.decode_by_names()?))),
+        None => Ok((None, None)),
+    }
+}
+
+fn test_get_path_impl(tarantool: &TarantoolContext) -> io::Result<Vec<Value>> {
+    let (id, paths): (u32, Vec<String>) = tarantool.decode_input_params()?;
+    let row = match tarantool.index_get(TEST_SPACE, PRIMARY_INDEX, &(id, ))? {
+        Some(row) => row,
+        None => return Ok(Vec::new()),
+    };
+    paths.iter().map(|path| row.get_path(path)).collect()
+}
+
+fn test_ddl_impl(tarantool: &TarantoolContext) -> io::Result<(Vec<String>, Vec<String>, usize, bool)> {
+    let (space_name, ): (String, ) = tarantool.decode_input_params()?;
+    let options = SpaceOptions {
+        format: vec![FieldInfo::new("id", "unsigned"), FieldInfo::new("name", "string")],
+        if_not_exists: true,
+        ..Default::default()
+    };
+    tarantool.create_space(&space_name, &options)?;
+    tarantool.create_space(&space_name, &options)?;
+    tarantool.create_index(&space_name, "primary", &IndexOptions::new(IndexType::Tree, vec![IndexPart::new(0, "unsigned")]))?;
+    let mut secondary = IndexOptions::new(IndexType::Tree, vec![IndexPart::new(1, "string")]);
+    secondary.unique = false;
+    tarantool.create_index(&space_name, "secondary", &secondary)?;
+    tarantool.insert(&space_name, &(1, "first"))?;
+    tarantool.rename_index(&space_name, "secondary", "by_name")?;
+
+    let renamed = format!("{}_renamed", space_name);
+    tarantool.rename_space(&space_name, &renamed)?;
+    let format = tarantool.space_info(&renamed)?.format.into_iter().map(|field| field.name).collect();
+    let indexes = tarantool.space_indexes(&renamed)?.into_iter().map(|index| index.name).collect();
+    let rows = tarantool.index_iterator_all(&renamed, "by_name")?.count();
+
+    tarantool.drop_index(&renamed, "missing", true)?;
+    let missing_space_error = tarantool.drop_space("missing_space", false).is_err();
+    tarantool.drop_space(&renamed, false)?;
+    Ok((format, indexes, rows, missing_space_error))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NestedData {
+    pub a: u64,
+    pub b: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NestedRow {
+    pub id: u32,
+    pub name: String,
+    pub data: NestedData,
+}
+
+/// insert row with nested struct written as map, map given as row is written as map and rejected by server
+fn test_insert_nested_maps_impl(tarantool: &TarantoolContext) -> io::Result<bool> {
+    let (row, ): (NestedRow, ) = tarantool.decode_input_params()?;
+    tarantool.insert_with(TEST_SPACE, &row, StructEncoding::NestedMaps)?;
+    let mut map_row = std::collections::BTreeMap::new();
+    map_row.insert("id", row.id + 1);
+    Ok(tarantool.insert_with(TEST_SPACE, &map_row, StructEncoding::NestedMaps).is_err())
+}
+
+fn test_insert_many_impl(tarantool: &TarantoolContext) -> io::Result<BatchResult> {
+    let (rows, chunk_size, stop_on_error): (Vec<RowTypeStruct>, usize, bool) = tarantool.decode_input_params()?;
+    let options = BatchOptions { chunk_size, stop_on_error, ..Default::default() };
+    tarantool.insert_many(TEST_SPACE, rows.iter(), &options)
+}
+
+fn test_delete_many_impl(tarantool: &TarantoolContext) -> io::Result<BatchResult> {
+    let (ids, ): (Vec<u32>, ) = tarantool.decode_input_params()?;
+    tarantool.delete_many(TEST_SPACE, PRIMARY_INDEX, ids.iter().map(|id| (id, )), &BatchOptions::default())
+}
+
+fn test_index_get_many_impl(tarantool: &TarantoolContext) -> io::Result<Vec<Option<RowTypeStruct>>> {
+    let (ids, dedupe, sort): (Vec<u32>, bool, bool) = tarantool.decode_input_params()?;
+    let keys: Vec<(u32, )> = ids.into_iter().map(|id| (id, )).collect();
+    tarantool.index_get_many(TEST_SPACE, PRIMARY_INDEX, &keys, &GetManyOptions { dedupe, sort })
+}
+
+/// RowTypeStruct after two schema upgrades, old rows have no tag and version
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RowTypeStructV2 {
+    pub id: u32,
+    pub name: String,
+    pub data: Option<TestStruct>,
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub version: u32,
+}
+
+fn test_decode_tolerant_impl(tarantool: &TarantoolContext) -> io::Result<(Vec<(u32, Option<String>, u32)>, Vec<(u32, String)>)> {
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for row in tarantool.index_iterator_all(TEST_SPACE, PRIMARY_INDEX)? {
+        let row = row?;
+        match row.decode_tolerant::<RowTypeStructV2>() {
+            Ok(v) => rows.push((v.id, v.tag, v.version)),
+            Err(e) => match e.get_ref().and_then(|e| e.downcast_ref::<FieldDecodeError>()) {
+                Some(e) => errors.push((e.field, e.name.unwrap_or_default().to_string())),
+                None => return Err(e),
+            },
+        }
+    }
+    Ok((rows, errors))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExtRow {
+    pub id: u32,
+    pub name: String,
+    pub uuid: Uuid,
+    pub price: Decimal,
+    pub created: Datetime,
+}
+
+/// field written as json text, its value goes through serde_json while row is written as msgpack
+pub struct JsonField<T>(pub T);
+
+impl<T: serde::Serialize> serde::Serialize for JsonField<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let json = serde_json::to_string(&self.0).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&json)
+    }
+}
+
+fn test_ext_types_impl(tarantool: &TarantoolContext) -> io::Result<(String, String, Uuid, Decimal, (u32, String), (u32, String))> {
+    let (id, uuid, price): (u32, Uuid, Decimal) = tarantool.decode_input_params()?;
+    let row = ExtRow { id, name: format!("ext-{}", id), uuid, price, created: Datetime::from_timestamp(1600000000, 0) };
+    tarantool.insert(TEST_SPACE, &row)?;
+    let stored: ExtRow = tarantool.index_get(TEST_SPACE, PRIMARY_INDEX, &(id, ))?.decode()?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "inserted row not found"))?;
+
+    //decimal serialized to json alone and inside a row must not leave its ext binary to rows written after it
+    serde_json::to_string(&price).map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+    tarantool.insert(TEST_SPACE, &(id + 1, format!("plain-{}", id + 1)))?;
+    tarantool.insert(TEST_SPACE, &(id + 2, JsonField(price)))?;
+    tarantool.insert(TEST_SPACE, &(id + 3, format!("plain-{}", id + 3)))?;
+    let plain: (u32, String) = tarantool.index_get(TEST_SPACE, PRIMARY_INDEX, &(id + 1, ))?.decode()?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "plain row not found"))?;
+    let after_json: (u32, String) = tarantool.index_get(TEST_SPACE, PRIMARY_INDEX, &(id + 3, ))?.decode()?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "plain row not found"))?;
+    Ok((stored.uuid.to_string(), stored.price.to_string(), stored.uuid, stored.price, plain, after_json))
+}
+
+/// copy all rows of one space to another without knowing their format, returns copied rows and json dump
+fn test_copy_space_impl(tarantool: &TarantoolContext) -> io::Result<(usize, Vec<String>, String)> {
+    let (from, to): (String, String) = tarantool.decode_input_params()?;
+    let mut rows = Vec::new();
+    for row in tarantool.index_iterator_all(&from, PRIMARY_INDEX)? {
+        rows.push(row?.to_value()?);
+    }
+    for row in rows.iter() {
+        tarantool.replace(&to, row)?;
+    }
+    let kinds = rows.first()
+        .map(|row| row.iter().map(|field| ValueKind::of(field).name().to_string()).collect())
+        .unwrap_or_default();
+    let dump = Value::Array(rows.iter().map(|row| row.to_json()).collect());
+    Ok((rows.len(), kinds, dump.to_string()))
+}
+
+/// build row in memory, change it with update ops and store only if counter stays under limit
+fn test_tuple_update_impl(tarantool: &TarantoolContext) -> io::Result<(Option<OwnedTuple>, OwnedTuple)> {
+    let (id, name, step, limit): (u32, String, u32, u32) = tarantool.decode_input_params()?;
+    let row = TupleBuilder::new()
+        .field(&id)
+        .field(&name)
+        .field(&0u32)
+        .build()?;
+    let updated = row.as_tuple().update(&(("+", 2, step), ("=", 1, format!("{}-updated", name))), IndexBase::Zero)?;
+    let upserted = row.as_tuple().upsert(&(("+", 2, step), ("+", 1, 1)), IndexBase::Zero)?;
+    let counter: u32 = updated.as_tuple().decode_field(2)?;
+    if counter >= limit {
+        return Ok((None, upserted));
+    }
+    tarantool.replace(TEST_SPACE, &updated)?;
+    Ok((Some(updated), upserted))
+}
+
+fn test_key_def_impl(tarantool: &TarantoolContext) -> io::Result<(Vec<u32>, usize, (String, ))> {
+    let (rows, name): (Vec<(u32, String)>, String) = tarantool.decode_input_params()?;
+    let key_def = tarantool.key_def(TEST_SPACE, SECONDARY_INDEX)?;
+    let mut tuples = Vec::new();
+    for (id, row_name) in rows.iter() {
+        tuples.push(TupleBuilder::new().field(id).field(row_name).build()?);
+    }
+    tuples.sort_by(|a, b| key_def.compare(&a.as_tuple(), &b.as_tuple()));
+    tuples.dedup_by(|a, b| key_def.compare(&a.as_tuple(), &b.as_tuple()) == Ordering::Equal);
+    let mut less = 0;
+    for tuple in tuples.iter() {
+        if key_def.compare_with_key(&tuple.as_tuple(), &(&name, ))? == Ordering::Less {
+            less += 1;
+        }
+    }
+    let ids = tuples.iter().map(|tuple| tuple.as_tuple().decode_field(0)).collect::<io::Result<Vec<u32>>>()?;
+    let first_key = match tuples.first() {
+        Some(tuple) => key_def.extract_key_as(&tuple.as_tuple())?,
+        None => (String::new(), ),
+    };
+    Ok((ids, less, first_key))
+}
+
+/// rows of several spaces merged in order of secondary index, duplicated names are skipped
+fn test_merger_impl(tarantool: &TarantoolContext) -> io::Result<Vec<(u32, String)>> {
+    let (spaces, limit): (Vec<String>, usize) = tarantool.decode_input_params()?;
+    let key_def = tarantool.key_def(TEST_SPACE, SECONDARY_INDEX)?;
+    let mut sources = Vec::new();
+    for space in spaces.iter() {
+        sources.push(tarantool.index_iterator_all(space, SECONDARY_INDEX)?);
+    }
+    let mut rows = Vec::new();
+    for row in Merger::new(&key_def, sources).dedup().limit(limit) {
+        rows.push(row?.as_tuple().decode_fields(&[0, 1])?);
+    }
+    Ok(rows)
+}
+
+/// report of test space rows grouped by region, fields are given by name and by number
+fn test_aggregate_impl(tarantool: &TarantoolContext) -> io::Result<Vec<(String, u64, Option<i64>, Option<i64>, Option<i64>, Option<f64>, u64, u64)>> {
+    let (min_id, skipped_region): (u32, String) = tarantool.decode_input_params()?;
+    tarantool.aggregate(TEST_SPACE, PRIMARY_INDEX)
+        .range(IteratorType::GE, &(min_id, ))
+        .filter(Predicate::ne("region", &skipped_region))
+        .yield_policy(YieldPolicy::EveryRows(2))
+        .group_by("region")
+        .count()
+        .sum("amount")
+        .min(3)
+        .max(3)
+        .avg("amount")
+        .count_distinct(1)
+        .count_distinct("score")
+        .run()
+}
+
+/// index size and statistics of fields estimated by random sample of sample_size tuples
+fn test_index_stats_impl(tarantool: &TarantoolContext) -> io::Result<(usize, bool, usize, usize, usize, f64)> {
+    let (sample_size, ): (usize, ) = tarantool.decode_input_params()?;
+    let len = tarantool.index_len(TEST_SPACE, PRIMARY_INDEX)?;
+    let bsize = tarantool.index_bsize(TEST_SPACE, PRIMARY_INDEX)?;
+    let mut sample_ids = Vec::new();
+    for row in tarantool.index_sample(TEST_SPACE, PRIMARY_INDEX, sample_size)? {
+        sample_ids.push(row.as_tuple().decode_field::<u32>(0)?);
+    }
+    sample_ids.sort_unstable();
+    sample_ids.dedup();
+    let names = tarantool.key_stats(TEST_SPACE, SECONDARY_INDEX, sample_size)?;
+    let groups = tarantool.field_stats(TEST_SPACE, PRIMARY_INDEX, &[2], sample_size)?;
+    Ok((len, bsize > 0, sample_ids.len(), names.distinct, groups.distinct, groups.fraction(&("x", ))?))
+}
+
+/// caller of the proc and its access to the space, write is checked with row level authorization helper
+fn test_session_impl(tarantool: &TarantoolContext) -> io::Result<(bool, String, String, bool, bool, bool, Option<String>)> {
+    let (space_name, ): (String, ) = tarantool.decode_input_params()?;
+    let session = tarantool.session_info()?;
+    let can_read = tarantool.has_privilege(&space_name, "read")?;
+    let write_error = match tarantool.check_privilege(&space_name, "write") {
+        Ok(()) => None,
+        Err(ref e) if e.kind() == io::ErrorKind::PermissionDenied => Some(e.to_string()),
+        //servers without box_access_check_space may not show privileges of roles
+        Err(ref e) if e.to_string().contains("not visible") => Some(e.to_string()),
+        Err(e) => return Err(e),
+    };
+    Ok((session.id > 0, session.user_name, session.effective_user_name, session.peer.is_some(), session.sync > 0, can_read, write_error))
+}
+
+/// push row count and then rows of test space in chunks before the proc returns
+fn test_session_push_impl(tarantool: &TarantoolContext) -> io::Result<usize> {
+    let (chunk_size, ): (usize, ) = tarantool.decode_input_params()?;
+    tarantool.session_push(&("start", tarantool.index_len(TEST_SPACE, PRIMARY_INDEX)?))?;
+    let rows = tarantool.index_iterator_all(TEST_SPACE, PRIMARY_INDEX)?.with_yield_policy(YieldPolicy::EveryRows(2))?;
+    tarantool.session_push_tuples(rows, chunk_size)
+}
+
+/// push values with lua box.session.push fallback, the way servers without box_session_push do
+fn test_session_push_lua_impl(tarantool: &TarantoolContext) -> io::Result<usize> {
+    let (values, ): (Vec<(String, u32)>, ) = tarantool.decode_input_params()?;
+    for value in &values {
+        let mut data = Vec::new();
+        serialize_to_vec(&mut data, value)?;
+        tarantool.session_push_data_lua(&data)?;
+    }
+    Ok(values.len())
+}
+
 fn test_min_max_count_impl(tarantool: &TarantoolContext) -> io::Result<(Option<RowTypeStruct>, Option<RowTypeStruct>, isize)> {
     let (start_name, ): (String, ) = tarantool.decode_input_params()?;
     let start_key = &(start_name, );
@@ -162,69 +538,89 @@ fn test_lua_call_impl(tarantool: &TarantoolContext) -> io::Result<(Option<TestSt
 }
 
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(remote = "Self")]
 pub struct CountryData {
+    #[serde(rename = "country-code")]
     pub country_code: u32,
     pub name: String,
     pub region: String,
+    #[serde(rename = "sub-region")]
     pub sub_region: String,
 }
 
-impl Serialize for CountryData {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
-        let mut map = serializer.serialize_map(Some(4))?;
-        map.serialize_entry("country-code", &self.country_code)?;
-        map.serialize_entry("name", &self.name)?;
-        map.serialize_entry("region", &self.region)?;
-        map.serialize_entry("sub-region", &self.sub_region)?;
-        map.end()
-    }
-}
+tarantool_struct_as_map!(CountryData);
 
 static COUNTRY_SPACE: &str = "countries";
 static COUNTRY_INDEX: &str = "primary";
 
-fn check_attr(search_str: &Option<String>, row: &TarantoolTuple, index: u32) -> io::Result<bool> {
+fn contains_filter(search_str: Option<String>, index: u32) -> Predicate {
     match search_str {
-        None => Ok(true),
-        Some(search_str_v) => {
-            let column_value: Option<String> = row.decode_field(index)?;
-            match column_value {
-                None => Ok(false),
-                Some(column_value_v) => {
-                    Ok(column_value_v.contains(search_str_v))
-                }
-            }
-        }
+        None => Predicate::all(Vec::new()),
+        Some(search_str) => Predicate::contains(index, &search_str),
     }
 }
 
 fn test_bench_impl(tarantool: &TarantoolContext) -> io::Result<Vec<CountryData>> {
     let (p_name, p_region, p_sub_region): (Option<String>, Option<String>, Option<String>) = tarantool.decode_input_params()?;
-    let p_name_l = p_name.map(|v|v.to_lowercase());
-    let p_region_l = p_region.map(|v|v.to_lowercase());
-    let p_sub_region_l = p_sub_region.map(|v|v.to_lowercase());
+    let filter = contains_filter(p_name.map(|v|v.to_lowercase()), 1)
+        .and(contains_filter(p_region.map(|v|v.to_lowercase()), 2))
+        .and(contains_filter(p_sub_region.map(|v|v.to_lowercase()), 3));
 
     let mut result: Vec<CountryData> = Vec::new();
-    for raw_row in tarantool.index_iterator_all(COUNTRY_SPACE, COUNTRY_INDEX)? {
-        let row = raw_row?;
-        if check_attr(&p_name_l, &row, 1)? &&
-            check_attr(&p_region_l, &row, 2)? &&
-            check_attr(&p_sub_region_l, &row, 3)? {
-            let row: CountryData = row.decode()?;
-            result.push(row);
-        }
-        tarantool.fiber_yield();
+    for row in tarantool.index_iterator_all(COUNTRY_SPACE, COUNTRY_INDEX)?.with_yield_policy(YieldPolicy::EveryMicros(500))?.filter_by(filter).typed() {
+        result.push(row?);
     };
 
     Ok(result)
 }
 
+/// rows of test space filtered by predicate on raw tuples, fields are given by name and by number
+fn test_predicate_impl(tarantool: &TarantoolContext) -> io::Result<Vec<(u32, String)>> {
+    let (prefix, excluded_id, pattern): (String, u32, String) = tarantool.decode_input_params()?;
+    let filter = Predicate::prefix("name", &prefix)
+        .and(Predicate::ne("id", &excluded_id))
+        .and(Predicate::regex(1, &pattern).or(Predicate::is_null(2)));
+    tarantool.index_iterator_all(TEST_SPACE, PRIMARY_INDEX)?
+        .filter_by(filter)
+        .decode_fields(&[0, 1])
+        .collect()
+}
+
 fn test_get_space_id_impl(tarantool: &TarantoolContext) -> io::Result<u32> {
     let (space_name,) : (String,) = tarantool.decode_input_params()?;
     return tarantool.get_space_id(space_name);
 }
 
+static MIGRATED_SPACE: &str = "migrated_space";
+
+fn create_migrated_space(tarantool: &TarantoolContext) -> io::Result<()> {
+    tarantool.create_space(MIGRATED_SPACE, &SpaceOptions {
+        format: vec![FieldInfo::new("id", "unsigned"), FieldInfo::new("name", "string")],
+        if_not_exists: true,
+        ..Default::default()
+    })?;
+    let mut primary = IndexOptions::new(IndexType::Tree, vec![IndexPart::new(0, "unsigned")]);
+    primary.if_not_exists = true;
+    tarantool.create_index(MIGRATED_SPACE, PRIMARY_INDEX, &primary)
+}
+
+fn fill_migrated_space(tarantool: &TarantoolContext) -> io::Result<()> {
+    for id in 1..=3u32 {
+        tarantool.insert(MIGRATED_SPACE, &(id, format!("migrated-{}", id)))?;
+    }
+    Ok(())
+}
+
+fn migrations() -> Vec<Migration> {
+    vec![
+        Migration::new_ddl(1, "create migrated_space", create_migrated_space),
+        Migration::new(2, "fill migrated_space", fill_migrated_space),
+    ]
+}
+
+tarantool_register_migrations!(migrations);
+
 tarantool_register_stored_procs! {
     test_insert => test_insert_impl,
     test_index_get => test_index_get_impl,
@@ -234,6 +630,31 @@ tarantool_register_stored_procs! {
     test_update => test_update_impl,
     test_upsert => test_upsert_impl,
     test_iterator => test_iterator_impl,
+    test_iterator_typed => test_iterator_typed_impl,
+    test_yield_scan => test_yield_scan_impl,
+    test_select => test_select_impl,
+    test_scan_page => test_scan_page_impl,
+    test_spatial => test_spatial_impl,
+    test_schema_info => test_schema_info_impl,
+    test_by_names => test_by_names_impl,
+    test_get_path => test_get_path_impl,
+    test_ddl => test_ddl_impl,
+    test_insert_many => test_insert_many_impl,
+    test_insert_nested_maps => test_insert_nested_maps_impl,
+    test_delete_many => test_delete_many_impl,
+    test_index_get_many => test_index_get_many_impl,
+    test_decode_tolerant => test_decode_tolerant_impl,
+    test_ext_types => test_ext_types_impl,
+    test_copy_space => test_copy_space_impl,
+    test_tuple_update => test_tuple_update_impl,
+    test_key_def => test_key_def_impl,
+    test_merger => test_merger_impl,
+    test_predicate => test_predicate_impl,
+    test_aggregate => test_aggregate_impl,
+    test_index_stats => test_index_stats_impl,
+    test_session => test_session_impl,
+    test_session_push => test_session_push_impl,
+    test_session_push_lua => test_session_push_lua_impl,
     test_min_max_count => test_min_max_count_impl,
     test_truncate => test_truncate_impl,
     test_lua_call => test_lua_call_impl,
@@ -30,12 +30,16 @@ box.cfg {
 local ffi = require('ffi')
 ffi.cdef[[
         void init_dictionaries_ffi();
+        int init_migrations_ffi();
     ]]
 rust = ffi.load('./libtarantool_rust_api_example.so')
 rust.init_dictionaries_ffi();
 local refresh_dict_fn = function() rust.init_dictionaries_ffi(); end;
 box.space._space:on_replace(refresh_dict_fn);
 box.space._index:on_replace(refresh_dict_fn);
+if rust.init_migrations_ffi() ~= 0 then
+    error(box.error.last())
+end
 
 local function grantRightsToFunction(fnName)
     box.schema.func.create(fnName, { language = 'C' })
@@ -55,13 +59,42 @@ local function bootstrap()
     grantRightsToFunction('libtarantool_rust_api_example.test_update');
     grantRightsToFunction('libtarantool_rust_api_example.test_upsert');
     grantRightsToFunction('libtarantool_rust_api_example.test_iterator');
+    grantRightsToFunction('libtarantool_rust_api_example.test_iterator_typed');
+    grantRightsToFunction('libtarantool_rust_api_example.test_yield_scan');
+    grantRightsToFunction('libtarantool_rust_api_example.test_select');
+    grantRightsToFunction('libtarantool_rust_api_example.test_scan_page');
+    grantRightsToFunction('libtarantool_rust_api_example.test_spatial');
+    grantRightsToFunction('libtarantool_rust_api_example.test_schema_info');
+    grantRightsToFunction('libtarantool_rust_api_example.test_by_names');
+    grantRightsToFunction('libtarantool_rust_api_example.test_get_path');
+    grantRightsToFunction('libtarantool_rust_api_example.test_ddl');
+    grantRightsToFunction('libtarantool_rust_api_example.run_migrations');
+    grantRightsToFunction('libtarantool_rust_api_example.test_insert_many');
+    grantRightsToFunction('libtarantool_rust_api_example.test_insert_nested_maps');
+    grantRightsToFunction('libtarantool_rust_api_example.test_delete_many');
+    grantRightsToFunction('libtarantool_rust_api_example.test_index_get_many');
+    grantRightsToFunction('libtarantool_rust_api_example.test_decode_tolerant');
+    grantRightsToFunction('libtarantool_rust_api_example.test_ext_types');
+    grantRightsToFunction('libtarantool_rust_api_example.test_copy_space');
+    grantRightsToFunction('libtarantool_rust_api_example.test_tuple_update');
+    grantRightsToFunction('libtarantool_rust_api_example.test_key_def');
+    grantRightsToFunction('libtarantool_rust_api_example.test_merger');
+    grantRightsToFunction('libtarantool_rust_api_example.test_predicate');
+    grantRightsToFunction('libtarantool_rust_api_example.test_aggregate');
+    grantRightsToFunction('libtarantool_rust_api_example.test_index_stats');
+    grantRightsToFunction('libtarantool_rust_api_example.test_session');
+    grantRightsToFunction('libtarantool_rust_api_example.test_session_push');
+    grantRightsToFunction('libtarantool_rust_api_example.test_session_push_lua');
     grantRightsToFunction('libtarantool_rust_api_example.test_min_max_count');
     grantRightsToFunction('libtarantool_rust_api_example.test_truncate');
     grantRightsToFunction('libtarantool_rust_api_example.test_lua_call');
     grantRightsToFunction('libtarantool_rust_api_example.test_get_space_id');
+
+    box.schema.user.create('reader', { password = 'reader' })
+    box.schema.user.grant('reader', 'execute', 'function', 'libtarantool_rust_api_example.test_session')
 end
 
-box.once('grants3', bootstrap)
+box.once('grants6', bootstrap)
 
 
 local function init_test_spaces()
@@ -87,7 +120,7 @@ local capi_connection = net_box:new(3301)
 
 
 local testPlan = tap.test("test plan")
-testPlan:plan(11)
+testPlan:plan(37)
 testPlan:test("insert test", function(test)
     test:plan(3)
     init_test_spaces()
@@ -104,6 +137,25 @@ testPlan:test("replace test", function(test)
     test:is(res[1], true, "call replace is ok")
     test:is_deeply(box.space.test_space:get(1):totable(), { 1, "replaced", { a = 2, b = "c" } }, "replace value is ok")
 end)
+testPlan:test("concurrent replace test", function(test)
+    test:plan(2)
+    init_test_spaces()
+    local futures = {}
+    for i = 1, 20 do
+        futures[i] = capi_connection:call('libtarantool_rust_api_example.test_replace', { i, 'concurrent-' .. i, { a = i, b = "b" .. i } }, { is_async = true })
+    end
+    local ok = true
+    for i = 1, 20 do
+        ok = ok and futures[i]:wait_result(10)[1] == true
+    end
+    test:is(ok, true, "concurrent calls ok")
+    local rows_ok = true
+    for i = 1, 20 do
+        local row = box.space.test_space:get(i)
+        rows_ok = rows_ok and row ~= nil and row[2] == 'concurrent-' .. i and row[3].a == i and row[3].b == "b" .. i
+    end
+    test:is(rows_ok, true, "rows serialized while other fibers yielded are ok")
+end)
 testPlan:test("index get test", function(test)
     test:plan(2)
     init_test_spaces()
@@ -159,6 +211,407 @@ testPlan:test("iterator test", function(test)
     local res = capi_connection:call('libtarantool_rust_api_example.test_iterator', { 6, ".*5.*" })
     test:is_deeply(res[1], { { 6, 'test-51row', { a = 1, b = "b" } },{ 7, 'test-52row', { a = 1, b = "b" } }}, "value found ok")
 end)
+testPlan:test("typed iterator test", function(test)
+    init_test_spaces()
+    box.space.test_space:put({ 1, 'test-1row', { a = 1, b = "b" } })
+    box.space.test_space:put({ 5, 'test-5row', { a = 1, b = "b" } })
+    box.space.test_space:put({ 6, 'test-51row', { a = 1, b = "b" } })
+    box.space.test_space:put({ 7, 'test-7row', { a = 1, b = "b" } })
+
+    test:plan(2)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_iterator_typed', { 5, ".*5.*" })[1]
+    test:is_deeply(res[1], { { 5, 'test-5row', { a = 1, b = "b" } }, { 6, 'test-51row', { a = 1, b = "b" } }, { 7, 'test-7row', { a = 1, b = "b" } } }, "typed rows ok")
+    test:is_deeply(res[2], { { 5, 'test-5row' }, { 6, 'test-51row' } }, "filtered fields ok")
+end)
+testPlan:test("select range test", function(test)
+    init_test_spaces()
+    for i = 1, 10 do
+        box.space.test_space:put({ i, 'test-' .. i .. 'row', { a = i, b = "b" } })
+    end
+
+    test:plan(3)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_select', { 3, 8, 1, 3 })[1]
+    test:is_deeply(res[1], { { 4, 'test-4row', { a = 4, b = "b" } }, { 5, 'test-5row', { a = 5, b = "b" } }, { 6, 'test-6row', { a = 6, b = "b" } } }, "range with offset and limit ok")
+    test:is_deeply(res[2], { { 7, 'test-7row', { a = 7, b = "b" } }, { 6, 'test-6row', { a = 6, b = "b" } }, { 5, 'test-5row', { a = 5, b = "b" } }, { 4, 'test-4row', { a = 4, b = "b" } }, { 3, 'test-3row', { a = 3, b = "b" } } }, "reverse range ok")
+    local res = capi_connection:call('libtarantool_rust_api_example.test_select', { 9, 20, 0, 10 })[1]
+    test:is(#res[1], 2, "range till the end ok")
+end)
+testPlan:test("scan page cursor test", function(test)
+    init_test_spaces()
+    for i = 1, 5 do
+        box.space.test_space:put({ i, 'test-' .. i .. 'row', { a = i, b = "b" } })
+    end
+
+    test:plan(8)
+    local page = capi_connection:call('libtarantool_rust_api_example.test_scan_page', { 'primary', msgpack.NULL, 2 })[1]
+    test:is_deeply({ page[1][1][1], page[1][2][1] }, { 1, 2 }, "first page ok")
+    page = capi_connection:call('libtarantool_rust_api_example.test_scan_page', { 'primary', page[2], 2 })[1]
+    test:is_deeply({ page[1][1][1], page[1][2][1] }, { 3, 4 }, "second page ok")
+    local token = page[2]
+    page = capi_connection:call('libtarantool_rust_api_example.test_scan_page', { 'primary', token, 2 })[1]
+    test:is_deeply({ page[1][1][1] }, { 5 }, "last page ok")
+    test:is(page[2], msgpack.NULL, "no cursor after last page")
+    box.space.test_space:create_index('third', { type = 'tree', parts = { 2, 'string' } })
+    local ok = pcall(capi_connection.call, capi_connection, 'libtarantool_rust_api_example.test_scan_page', { 'primary', token, 2 })
+    test:is(ok, false, "token rejected after schema change")
+    ok = pcall(capi_connection.call, capi_connection, 'libtarantool_rust_api_example.test_scan_page', { 'primary', 'garbage', 2 })
+    test:is(ok, false, "garbage token rejected")
+
+    init_test_spaces()
+    box.space.test_space.index.secondary:drop()
+    box.space.test_space:create_index('grouped', { type = 'tree', unique = false, parts = { 2, 'string' } })
+    for i = 1, 7 do
+        box.space.test_space:put({ i, i <= 5 and 'group-1' or 'group-2', { a = i, b = "b" } })
+    end
+    local ids = {}
+    token = msgpack.NULL
+    repeat
+        page = capi_connection:call('libtarantool_rust_api_example.test_scan_page', { 'grouped', token, 2 })[1]
+        for _, row in ipairs(page[1]) do
+            table.insert(ids, row[1])
+        end
+        token = page[2]
+    until token == msgpack.NULL
+    test:is_deeply(ids, { 1, 2, 3, 4, 5, 6, 7 }, "equal keys of non-unique index kept between pages")
+    page = capi_connection:call('libtarantool_rust_api_example.test_scan_page', { 'grouped', msgpack.NULL, 3 })[1]
+    box.space.test_space:delete(3)
+    page = capi_connection:call('libtarantool_rust_api_example.test_scan_page', { 'grouped', page[2], 3 })[1]
+    test:is_deeply({ page[1][1][1], page[1][2][1], page[1][3][1] }, { 4, 5, 6 }, "scan resumed after deleted last row")
+end)
+testPlan:test("yield scan test", function(test)
+    init_test_spaces()
+    box.space.test_space.index.secondary:drop()
+    box.space.test_space:create_index('grouped', { type = 'tree', unique = false, parts = { 2, 'string' } })
+    for i = 1, 7 do
+        box.space.test_space:put({ i, i <= 5 and 'group-1' or 'group-2', { a = i, b = "b" } })
+    end
+    local function scan(iterator, key, ddl_after_rows)
+        if box.space.test_space.index.yield_ddl ~= nil then
+            box.space.test_space.index.yield_ddl:drop()
+        end
+        return capi_connection:call('libtarantool_rust_api_example.test_yield_scan', { iterator, key, ddl_after_rows })[1]
+    end
+
+    test:plan(6)
+    test:is_deeply(scan('ALL', {}, 0), { 1, 2, 3, 4, 5, 6, 7 }, "yield without schema change ok")
+    test:is_deeply(scan('ALL', {}, 3), { 1, 2, 3, 4, 5, 6, 7 }, "equal keys kept after schema change in the middle of them")
+    test:is_deeply(scan('EQ', { 'group-1' }, 3), { 1, 2, 3, 4, 5 }, "EQ continued after schema change")
+    test:is_deeply(scan('REQ', { 'group-1' }, 2), { 5, 4, 3, 2, 1 }, "REQ continued after schema change")
+    test:is_deeply(scan('LE', { 'group-2' }, 1), { 7, 6, 5, 4, 3, 2, 1 }, "LE continued after schema change")
+    test:is_deeply(scan('EQ', { 'group-2' }, 2), { 6, 7 }, "EQ ended after schema change on last row")
+end)
+testPlan:test("rtree and bitset test", function(test)
+    if (box.space.geo_space ~= nil) then
+        box.space.geo_space:drop();
+    end
+    box.schema.create_space('geo_space', { engine = 'memtx' })
+    box.space.geo_space:create_index('primary', { type = 'tree', parts = { 1, 'unsigned' } })
+    box.space.geo_space:create_index('spatial', { type = 'rtree', unique = false, parts = { 2, 'array' } })
+    box.space.geo_space:create_index('flags', { type = 'bitset', unique = false, parts = { 3, 'unsigned' } })
+    box.space.geo_space:put({ 1, { 1, 1 }, 3 })
+    box.space.geo_space:put({ 2, { 2, 2 }, 1 })
+    box.space.geo_space:put({ 3, { 10, 10 }, 7 })
+
+    test:plan(3)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_spatial', { { 0, 0, 5, 5 }, { 9, 9 }, 3 })[1]
+    table.sort(res[1])
+    table.sort(res[3])
+    test:is_deeply(res[1], { 1, 2 }, "points within rectangle ok")
+    test:is_deeply(res[2], { 3, 2 }, "nearest points ok")
+    test:is_deeply(res[3], { 1, 3 }, "bitset all set ok")
+end)
+testPlan:test("schema introspection test", function(test)
+    init_test_spaces()
+    box.space.test_space:format({ { name = 'id', type = 'number' }, { name = 'name', type = 'string' }, { name = 'data', type = 'any', is_nullable = true } })
+
+    test:plan(5)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_schema_info', {})[1]
+    test:is(res[1], 'memtx', "engine ok")
+    test:is_deeply(res[2], { 'id', 'name', 'data' }, "format ok")
+    test:is_deeply(res[3], { { 'primary', true, { 0 } }, { 'secondary', true, { 1 } } }, "indexes ok")
+    test:is(res[4], msgpack.NULL, "struct matches format")
+    box.space.test_space:format({ { name = 'id', type = 'number' }, { name = 'title', type = 'string' } })
+    res = capi_connection:call('libtarantool_rust_api_example.test_schema_info', {})[1]
+    test:isnt(res[4], msgpack.NULL, "struct mismatch detected")
+end)
+testPlan:test("access by field names test", function(test)
+    init_test_spaces()
+    box.space.test_space:format({ { name = 'id', type = 'number' }, { name = 'name', type = 'string' }, { name = 'data', type = 'any', is_nullable = true } })
+
+    test:plan(3)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_by_names', { 7, 'named-row' })[1]
+    test:is(res[1], 'named-row', "field got by name")
+    test:is_deeply(res[2], { 'named-row', 7 }, "struct decoded by names")
+    test:is_deeply(box.space.test_space:get(7), { 7, 'named-row' }, "struct encoded by names")
+end)
+testPlan:test("json path access test", function(test)
+    init_test_spaces()
+    box.space.test_space:format({ { name = 'id', type = 'number' }, { name = 'name', type = 'string' }, { name = 'data', type = 'any', is_nullable = true } })
+    box.space.test_space:put({ 1, 'test-1row', { a = 1, b = { 10, { name = 'nested' } } } })
+
+    test:plan(4)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_get_path', { 1, { 'data.b[2].name', '[3].a', 'data.b[1]', 'data.c' } })[1]
+    test:is(res[1], 'nested', "nested value by field name path")
+    test:is(res[2], 1, "nested value by field number path")
+    test:is(res[3], 10, "array element")
+    test:is(res[4], msgpack.NULL, "absent value is nil")
+end)
+testPlan:test("ddl test", function(test)
+    test:plan(5)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_ddl', { 'ddl_space' })[1]
+    test:is_deeply(res[1], { 'id', 'name' }, "space created with format")
+    test:is_deeply(res[2], { 'primary', 'by_name' }, "indexes created and renamed")
+    test:is(res[3], 1, "row inserted into new space")
+    test:ok(res[4], "drop of missing space fails")
+    test:is(box.space.ddl_space_renamed, nil, "space dropped")
+end)
+testPlan:test("migrations test", function(test)
+    test:plan(7)
+    test:is(box.space._rust_migrations:count(), 2, "migrations applied and recorded at init")
+    test:is(box.space.migrated_space:count(), 3, "data backfilled")
+    local res = capi_connection:call('libtarantool_rust_api_example.run_migrations', {})[1]
+    test:is_deeply(res, {}, "applied migrations are skipped")
+    box.space._rust_migrations:insert({ 99, 'unknown migration', 0 })
+    local ok = pcall(capi_connection.call, capi_connection, 'libtarantool_rust_api_example.run_migrations', {})
+    test:ok(not ok, "unknown applied migration refused")
+    box.space._rust_migrations:drop()
+    box.space.migrated_space:drop()
+
+    local first = capi_connection:call('libtarantool_rust_api_example.run_migrations', {}, { is_async = true })
+    local second = capi_connection:call('libtarantool_rust_api_example.run_migrations', {}, { is_async = true })
+    local applied = { first:wait_result(10)[1], second:wait_result(10)[1] }
+    table.sort(applied, function(a, b) return #a < #b end)
+    test:is_deeply(applied, { {}, { 1, 2 } }, "concurrent run waits for the first one")
+    test:is(box.space._rust_migrations:count(), 2, "migrations recorded once")
+    test:is(box.space.migrated_space:count(), 3, "data backfilled once")
+end)
+testPlan:test("nested maps encoding test", function(test)
+    init_test_spaces()
+
+    test:plan(3)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_insert_nested_maps', { { 1, 'nested', { 7, 'x' } } })
+    test:is(res[1], true, "map given as row is not turned into array")
+    test:is_deeply(box.space.test_space:get(1):totable(), { 1, 'nested', { a = 7, b = 'x' } }, "row is array, nested struct is map")
+    test:is(box.space.test_space:get(2), nil, "map row not inserted")
+end)
+testPlan:test("batch write test", function(test)
+    init_test_spaces()
+    box.space.test_space:put({ 2, 'test-2row', { a = 1, b = "b" } })
+
+    test:plan(6)
+    local rows = { { 1, 'test-1row', msgpack.NULL }, { 2, 'test-2row-dup', msgpack.NULL }, { 3, 'test-3row', msgpack.NULL } }
+    local res = capi_connection:call('libtarantool_rust_api_example.test_insert_many', { rows, 2, false })[1]
+    test:is(res[1], 2, "rows written")
+    test:is(res[2][1][1], 1, "duplicate row reported")
+    test:is(box.space.test_space:count(), 3, "batch not aborted by duplicate")
+    local ok = pcall(capi_connection.call, capi_connection, 'libtarantool_rust_api_example.test_insert_many', { { { 4, 'test-4row', msgpack.NULL }, { 1, 'test-1row-dup', msgpack.NULL } }, 10, true })
+    test:ok(not ok, "batch stopped on error")
+    test:is(box.space.test_space:get(4), nil, "chunk rolled back")
+    res = capi_connection:call('libtarantool_rust_api_example.test_delete_many', { { 1, 2, 3, 4 } })[1]
+    test:is(box.space.test_space:count(), 0, "rows deleted")
+end)
+testPlan:test("index get many test", function(test)
+    init_test_spaces()
+    box.space.test_space:put({ 1, 'test-1row', { a = 1, b = "b" } })
+    box.space.test_space:put({ 2, 'test-2row', { a = 1, b = "b" } })
+    box.space.test_space:put({ 3, 'test-3row', { a = 1, b = "b" } })
+
+    test:plan(4)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_index_get_many', { { 3, 5, 1 }, false, false })[1]
+    test:is(res[1][2], 'test-3row', "first key found")
+    test:is(res[2], msgpack.NULL, "absent key is nil")
+    test:is(res[3][2], 'test-1row', "results are in order of keys")
+    res = capi_connection:call('libtarantool_rust_api_example.test_index_get_many', { { 2, 1, 2, 9, 1 }, true, true })[1]
+    test:is_deeply({ res[1][1], res[2][1], res[3][1], res[4], res[5][1] }, { 2, 1, 2, msgpack.NULL, 1 }, "deduped and sorted lookups keep order of keys")
+end)
+testPlan:test("tolerant decoding test", function(test)
+    init_test_spaces()
+    box.space.test_space:put({ 1, 'test-1row' })
+    box.space.test_space:put({ 2, 'test-2row', { a = 1, b = "b" }, 'tagged', 2, 'newer field' })
+    box.space.test_space:put({ 3, 'test-3row', 'not a struct' })
+
+    test:plan(4)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_decode_tolerant', {})[1]
+    test:is(res[1][1][2], msgpack.NULL, "missing trailing option field is none")
+    test:is(res[1][1][3], 0, "missing trailing field with default is default")
+    test:is_deeply(res[1][2], { 2, 'tagged', 2 }, "extra trailing field is ignored")
+    test:is_deeply(res[2], { { 2, 'data' } }, "mismatch reports field number and name")
+end)
+testPlan:test("ext types test", function(test)
+    init_test_spaces()
+    local uuid = require('uuid')
+    local decimal = require('decimal')
+    local id = uuid.fromstr('64d22e4d-ac92-4a23-899a-e59f34af5479')
+
+    test:plan(7)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_ext_types', { 1, id, decimal.new('12.30') })
+    test:is(res[1], '64d22e4d-ac92-4a23-899a-e59f34af5479', "uuid decoded from ext")
+    test:is(res[2], '12.30', "decimal decoded from ext")
+    test:is(res[3], id, "uuid returned as ext")
+    test:is(res[4], decimal.new('12.30'), "decimal returned as ext")
+    local row = box.space.test_space:get(1)
+    test:ok(uuid.is_uuid(row[3]) and decimal.is_decimal(row[4]), "row stored with ext values")
+    test:is_deeply(res[5], { 2, 'plain-2' }, "plain row intact after decimal serialized to json")
+    test:is_deeply(res[6], { 4, 'plain-4' }, "plain row intact after decimal serialized to json inside row")
+end)
+testPlan:test("dynamic tuple test", function(test)
+    init_test_spaces()
+    if (box.space.test_space_copy ~= nil) then
+        box.space.test_space_copy:drop()
+    end
+    box.schema.create_space('test_space_copy', { engine = 'memtx' })
+    box.space.test_space_copy:create_index('primary', { type = 'tree', parts = { 1, 'number' } })
+    local id = require('uuid').fromstr('64d22e4d-ac92-4a23-899a-e59f34af5479')
+    box.space.test_space:put({ 1, 'test-1row', id, -1.5, { a = 1 } })
+    box.space.test_space:put({ 2, 'test-2row' })
+
+    test:plan(4)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_copy_space', { 'test_space', 'test_space_copy' })
+    test:is(res[1], 2, "all rows copied")
+    test:is_deeply(res[2], { 'integer', 'string', 'uuid', 'double', 'map' }, "field kinds")
+    test:is_deeply(box.space.test_space_copy:get(1):totable(), box.space.test_space:get(1):totable(), "copied row is equal")
+    test:is_deeply(json.decode(res[3])[1], { 1, 'test-1row', '64d22e4d-ac92-4a23-899a-e59f34af5479', -1.5, { a = 1 } }, "json dump")
+    box.space.test_space_copy:drop()
+end)
+testPlan:test("tuple update in memory test", function(test)
+    init_test_spaces()
+
+    test:plan(4)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_tuple_update', { 1, 'test-1row', 5, 10 })
+    test:is_deeply(res[1], { 1, 'test-1row-updated', 5 }, "update ops applied")
+    test:is_deeply(res[2], { 1, 'test-1row', 5 }, "failed upsert op skipped")
+    test:is_deeply(box.space.test_space:get(1):totable(), { 1, 'test-1row-updated', 5 }, "updated row stored")
+    res = capi_connection:call('libtarantool_rust_api_example.test_tuple_update', { 2, 'test-2row', 20, 10 })
+    test:is(box.space.test_space:get(2), nil, "row over limit not stored")
+end)
+testPlan:test("key def test", function(test)
+    init_test_spaces()
+
+    test:plan(3)
+    local rows = { { 3, 'test-3row' }, { 1, 'test-1row' }, { 4, 'test-1row' }, { 2, 'test-2row' } }
+    local res = capi_connection:call('libtarantool_rust_api_example.test_key_def', { rows, 'test-3' })
+    test:is_deeply(res[1], { 1, 2, 3 }, "rows sorted and deduplicated by secondary index")
+    test:is(res[2], 2, "two rows less than key")
+    test:is_deeply(res[3], { 'test-1row' }, "key extracted")
+end)
+testPlan:test("merger test", function(test)
+    init_test_spaces()
+    if (box.space.test_space_part ~= nil) then
+        box.space.test_space_part:drop()
+    end
+    box.schema.create_space('test_space_part', { engine = 'memtx' })
+    box.space.test_space_part:create_index('primary', { type = 'tree', parts = { 1, 'number' } })
+    box.space.test_space_part:create_index('secondary', { type = 'tree', parts = { 2, 'string' } })
+    box.space.test_space:put({ 1, 'test-a' })
+    box.space.test_space:put({ 3, 'test-c' })
+    box.space.test_space:put({ 5, 'test-e' })
+    box.space.test_space_part:put({ 2, 'test-b' })
+    box.space.test_space_part:put({ 4, 'test-c' })
+    box.space.test_space_part:put({ 6, 'test-f' })
+
+    test:plan(2)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_merger', { { 'test_space', 'test_space_part' }, 10 })
+    test:is_deeply(res[1], { { 1, 'test-a' }, { 2, 'test-b' }, { 3, 'test-c' }, { 5, 'test-e' }, { 6, 'test-f' } }, "rows merged in order without duplicates")
+    res = capi_connection:call('libtarantool_rust_api_example.test_merger', { { 'test_space', 'test_space_part' }, 2 })
+    test:is_deeply(res[1], { { 1, 'test-a' }, { 2, 'test-b' } }, "merge limited")
+    box.space.test_space_part:drop()
+end)
+testPlan:test("predicate filter test", function(test)
+    init_test_spaces()
+    box.space.test_space:format({ { name = 'id', type = 'number' }, { name = 'name', type = 'string' }, { name = 'data', type = 'any', is_nullable = true } })
+    box.space.test_space:put({ 1, 'test-a1', { a = 1 } })
+    box.space.test_space:put({ 2, 'test-a2' })
+    box.space.test_space:put({ 3, 'test-b3' })
+    box.space.test_space:put({ 4, 'test-a4', { a = 1 } })
+    box.space.test_space:put({ 5, 'test-a5' })
+
+    test:plan(2)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_predicate', { 'test-a', 2, '4$' })
+    test:is_deeply(res[1], { { 4, 'test-a4' }, { 5, 'test-a5' } }, "rows filtered")
+    local ok = pcall(capi_connection.call, capi_connection, 'libtarantool_rust_api_example.test_predicate', { 'test-a', 2, '(' })
+    test:is(ok, false, "invalid regex reported")
+end)
+testPlan:test("aggregate test", function(test)
+    init_test_spaces()
+    box.space.test_space:format({ { name = 'id', type = 'number' }, { name = 'name', type = 'string' }, { name = 'region', type = 'string' }, { name = 'amount', type = 'integer', is_nullable = true }, { name = 'score', type = 'number', is_nullable = true } })
+    box.space.test_space:put({ 1, 'a', 'eu', 10 })
+    box.space.test_space:put({ 2, 'b', 'us', 5, 1 })
+    box.space.test_space:put({ 3, 'a', 'eu', 20, 2.5 })
+    box.space.test_space:put({ 4, 'c', 'eu', msgpack.NULL, 2.5 })
+    box.space.test_space:put({ 5, 'd', 'skip', 1 })
+    box.space.test_space:put({ 6, 'e', 'us', 7, ffi.cast('double', 1) })
+
+    test:plan(1)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_aggregate', { 2, 'skip' })
+    test:is_deeply(res[1], { { 'us', 2, 12, 5, 7, 6, 2, 1 }, { 'eu', 2, 20, 20, 20, 20, 2, 1 } }, "rows grouped and aggregated, 1 and 1.0 are one distinct value")
+end)
+testPlan:test("index stats and sample test", function(test)
+    init_test_spaces()
+    for i = 1, 10 do
+        box.space.test_space:put({ i, 'test-' .. i .. 'row', i % 2 == 0 and 'x' or 'y' })
+    end
+
+    test:plan(6)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_index_stats', { 4 })
+    test:is(res[1], 10, "index len")
+    test:is(res[2], true, "index bsize")
+    test:is(res[3], 4, "sample of distinct tuples")
+    res = capi_connection:call('libtarantool_rust_api_example.test_index_stats', { 100 })
+    test:is(res[4], 10, "distinct keys of unique index")
+    test:is(res[5], 2, "distinct values of field")
+    test:is(res[6], 0.5, "fraction of value")
+end)
+testPlan:test("session and privileges test", function(test)
+    init_test_spaces()
+    box.schema.user.grant('reader', 'read', 'space', 'test_space')
+
+    test:plan(10)
+    local res = capi_connection:call('libtarantool_rust_api_example.test_session', { 'test_space' })
+    test:is(res[1], true, "session id")
+    test:is(res[2], 'guest', "session user")
+    test:is(res[4], true, "peer of remote session")
+    test:is(res[5], true, "sync of request")
+    test:is(res[7], nil, "guest can write")
+    local reader_connection = net_box:new('reader:reader@localhost:3301')
+    res = reader_connection:call('libtarantool_rust_api_example.test_session', { 'test_space' })
+    test:is(res[2], 'reader', "session user of other connection")
+    test:is(res[3], 'reader', "effective user")
+    test:is(res[6], true, "reader can read")
+    test:isnt(res[7], nil, "reader can't write")
+    box.schema.role.create('test_writer', { if_not_exists = true })
+    box.schema.role.grant('test_writer', 'write', 'space', 'test_space', { if_not_exists = true })
+    box.schema.user.grant('reader', 'test_writer', nil, nil, { if_not_exists = true })
+    res = reader_connection:call('libtarantool_rust_api_example.test_session', { 'test_space' })
+    test:ok(res[7] == nil or string.find(res[7], 'not visible') ~= nil, "write of role is granted or reported as unknown, not denied")
+    box.schema.user.revoke('reader', 'test_writer')
+    reader_connection:close()
+end)
+testPlan:test("session push test", function(test)
+    init_test_spaces()
+    for i = 1, 5 do
+        box.space.test_space:put({ i, 'test-' .. i .. 'row' })
+    end
+
+    test:plan(2)
+    local messages = {}
+    local res = capi_connection:call('libtarantool_rust_api_example.test_session_push', { 2 },
+        { on_push = function(ctx, message) table.insert(ctx, message) end, on_push_ctx = messages })
+    test:is(res[1], 5, "all rows pushed")
+    test:is_deeply(messages, {
+        { 'start', 5 },
+        { { 1, 'test-1row' }, { 2, 'test-2row' } },
+        { { 3, 'test-3row' }, { 4, 'test-4row' } },
+        { { 5, 'test-5row' } },
+    }, "rows pushed in chunks before result")
+end)
+testPlan:test("session push lua fallback test", function(test)
+    test:plan(2)
+    local messages = {}
+    local res = capi_connection:call('libtarantool_rust_api_example.test_session_push_lua', { { { 'a', 1 }, { 'b', 2 } } },
+        { on_push = function(ctx, message) table.insert(ctx, message) end, on_push_ctx = messages })
+    test:is(res[1], 2, "all values pushed")
+    test:is_deeply(messages, { { 'a', 1 }, { 'b', 2 } }, "values pushed by lua received in on_push")
+end)
 testPlan:test("index min max count test", function(test)
     init_test_spaces()
     box.space.test_space:put({ 1, 'test-1row', { a = 1, b = "b" } })
@@ -7,8 +7,12 @@ extern crate rmpv;
 extern crate rmp_serde;
 extern crate rmp_serialize;
 extern crate serde;
+#[macro_use]
+extern crate serde_derive;
 extern crate rmp;
 extern crate base64;
+extern crate serde_json;
+extern crate regex;
 
 extern crate log;
 extern crate env_logger;
@@ -0,0 +1,373 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use crate::tarantool::api::*;
+use crate::tarantool::internal::*;
+use crate::tarantool::msgpack::*;
+use crate::tarantool::predicate::{compare_scalars, read_scalar, Scalar};
+
+const NIL_FIELD: [u8; 1] = [MSGPACK_NIL];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum AggKind {
+    Count,
+    Sum,
+    Min,
+    Max,
+    Avg,
+    CountDistinct,
+}
+
+#[derive(Debug, Clone)]
+struct AggSpec {
+    kind: AggKind,
+    /// None for count of rows
+    field: Option<FieldRef>,
+}
+
+/// running value of one aggregate in one group, nil values of fields are skipped
+#[derive(Debug)]
+enum AggState {
+    Count(u64),
+    Sum { int: i128, float: f64, is_float: bool, count: u64 },
+    Min(Option<Vec<u8>>),
+    Max(Option<Vec<u8>>),
+    Avg { sum: f64, count: u64 },
+    Distinct(HashSet<Vec<u8>>),
+}
+
+impl AggState {
+    fn new(kind: AggKind) -> AggState {
+        match kind {
+            AggKind::Count => AggState::Count(0),
+            AggKind::Sum => AggState::Sum { int: 0, float: 0.0, is_float: false, count: 0 },
+            AggKind::Min => AggState::Min(None),
+            AggKind::Max => AggState::Max(None),
+            AggKind::Avg => AggState::Avg { sum: 0.0, count: 0 },
+            AggKind::CountDistinct => AggState::Distinct(HashSet::new()),
+        }
+    }
+
+    fn add(&mut self, field_no: u32, data: &[u8]) -> io::Result<()> {
+        let value = read_scalar(data)?;
+        if value == Scalar::Nil {
+            if let AggState::Count(count) = self {
+                *count += 1;
+            }
+            return Ok(());
+        }
+        match self {
+            AggState::Count(count) => *count += 1,
+            AggState::Sum { int, float, is_float, count } => {
+                match value {
+                    Scalar::Int(v) => *int += v,
+                    Scalar::Float(v) => {
+                        *float += v;
+                        *is_float = true;
+                    }
+                    _ => return not_a_number(field_no),
+                }
+                *count += 1;
+            }
+            AggState::Avg { sum, count } => {
+                match value {
+                    Scalar::Int(v) => *sum += v as f64,
+                    Scalar::Float(v) => *sum += v,
+                    _ => return not_a_number(field_no),
+                }
+                *count += 1;
+            }
+            AggState::Min(best) => replace_if(best, data, value, field_no, Ordering::Less)?,
+            AggState::Max(best) => replace_if(best, data, value, field_no, Ordering::Greater)?,
+            AggState::Distinct(values) => {
+                let mut normalized = Vec::with_capacity(data.len());
+                write_normalized(&mut normalized, data)?;
+                values.insert(normalized);
+            }
+        }
+        Ok(())
+    }
+
+    fn write(&self, buf: &mut Vec<u8>) {
+        match self {
+            AggState::Count(count) => write_u64(buf, *count),
+            AggState::Sum { count: 0, .. } | AggState::Avg { count: 0, .. } | AggState::Min(None) | AggState::Max(None) => buf.push(MSGPACK_NIL),
+            AggState::Sum { int, float, is_float: true, .. } => write_f64(buf, *int as f64 + float),
+            AggState::Sum { int, .. } => {
+                if *int >= 0 && *int <= u64::MAX as i128 {
+                    write_u64(buf, *int as u64);
+                } else if *int >= i64::MIN as i128 {
+                    let _ = rmp::encode::write_sint(buf, *int as i64);
+                } else {
+                    write_f64(buf, *int as f64);
+                }
+            }
+            AggState::Avg { sum, count } => write_f64(buf, sum / *count as f64),
+            AggState::Min(Some(value)) | AggState::Max(Some(value)) => buf.extend_from_slice(value),
+            AggState::Distinct(values) => write_u64(buf, values.len() as u64),
+        }
+    }
+}
+
+fn write_u64(buf: &mut Vec<u8>, value: u64) {
+    let _ = rmp::encode::write_uint(buf, value);
+}
+
+fn write_f64(buf: &mut Vec<u8>, value: f64) {
+    let _ = rmp::encode::write_f64(buf, value);
+}
+
+/// write value so that equal scalars have equal bytes: minimal ints, whole floats as ints, minimal headers of
+/// strings and binaries; arrays, maps and ext values are written as they are
+fn write_normalized(buf: &mut Vec<u8>, data: &[u8]) -> io::Result<()> {
+    match read_scalar(data)? {
+        Scalar::Int(v) => write_int(buf, v),
+        Scalar::Float(v) if v.fract() == 0.0 && v >= i64::MIN as f64 && v < u64::MAX as f64 => write_int(buf, v as i128),
+        Scalar::Float(v) => write_f64(buf, v),
+        Scalar::Str(v) => {
+            let _ = rmp::encode::write_str_len(buf, v.len() as u32);
+            buf.extend_from_slice(v);
+        }
+        Scalar::Bin(v) => write_bin(buf, v),
+        Scalar::Nil | Scalar::Bool(_) | Scalar::Other => buf.extend_from_slice(&data[..value_len(data)?]),
+    }
+    Ok(())
+}
+
+fn write_int(buf: &mut Vec<u8>, value: i128) {
+    if value >= 0 {
+        write_u64(buf, value as u64);
+    } else {
+        let _ = rmp::encode::write_sint(buf, value as i64);
+    }
+}
+
+fn not_a_number<T>(field_no: u32) -> io::Result<T> {
+    Err(make_error(format!("Field {} is not a number, it can't be summed!", field_no), false))
+}
+
+fn replace_if(best: &mut Option<Vec<u8>>, data: &[u8], value: Scalar, field_no: u32, wanted: Ordering) -> io::Result<()> {
+    let replace = match best {
+        None => true,
+        Some(best) => match compare_scalars(value, read_scalar(best)?) {
+            Some(ordering) => ordering == wanted,
+            None => return Err(make_error(format!("Values of field {} have different types, they can't be compared!", field_no), false)),
+        },
+    };
+    if replace {
+        *best = Some(data.to_vec());
+    }
+    Ok(())
+}
+
+/// Builder of aggregation over index range, rows are grouped by fields and folded without decoding
+///
+/// every result row is array of group fields followed by aggregates in order they were added, decoded as T,
+/// groups come in order of their first row and have its group fields; without group_by there is always one result row
+///
+/// group fields and count_distinct compare numbers by value (1 and 1.0 are equal) and strings by bytes,
+/// arrays, maps and ext values are equal only if they are encoded the same way
+///
+/// sum of integers is integer, avg is double, sum, avg, min and max of no values are nil
+///
+/// # Examples
+///
+/// let report: Vec<(String, u64, Option<i64>, Option<f64>, u64)> = tarantool.aggregate(ORDERS, "created")
+///     .range(IteratorType::GE, &(from, ))
+///     .filter(Predicate::eq("status", &"paid"))
+///     .yield_policy(YieldPolicy::EveryMicros(500))
+///     .group_by("region")
+///     .count()
+///     .sum("amount")
+///     .avg("amount")
+///     .count_distinct("customer_id")
+///     .run()?;
+///
+#[derive(Debug)]
+pub struct Aggregate<'ctx> {
+    ctx: &'ctx TarantoolContext,
+    space_name: String,
+    index_name: String,
+    iterator_type: IteratorType,
+    key_data: Vec<u8>,
+    filter: Option<Predicate>,
+    yield_policy: YieldPolicy,
+    group_by: Vec<FieldRef>,
+    aggregates: Vec<AggSpec>,
+    error: Option<io::Error>,
+}
+
+impl<'ctx> Aggregate<'ctx> {
+    fn new(ctx: &'ctx TarantoolContext, space_name: String, index_name: String) -> Aggregate<'ctx> {
+        Aggregate {
+            ctx,
+            space_name,
+            index_name,
+            iterator_type: IteratorType::ALL,
+            key_data: vec![0x90],
+            filter: None,
+            yield_policy: YieldPolicy::Never,
+            group_by: Vec::new(),
+            aggregates: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// rows of index iterator with key, all rows of index by default
+    pub fn range<SER: Serialize>(mut self, iterator_type: IteratorType, key: &SER) -> Self {
+        let mut key_data = Vec::new();
+        match serialize_to_vec(&mut key_data, key) {
+            Ok(_) => {
+                self.iterator_type = iterator_type;
+                self.key_data = key_data;
+            }
+            Err(e) => {
+                self.error.get_or_insert(e);
+            }
+        }
+        self
+    }
+
+    /// only rows matching predicate are aggregated, filters are combined with and
+    pub fn filter(mut self, predicate: Predicate) -> Self {
+        self.filter = Some(match self.filter.take() {
+            Some(filter) => filter.and(predicate),
+            None => predicate,
+        });
+        self
+    }
+
+    pub fn yield_policy(mut self, policy: YieldPolicy) -> Self {
+        self.yield_policy = policy;
+        self
+    }
+
+    /// add field to group key
+    pub fn group_by<F: Into<FieldRef>>(mut self, field: F) -> Self {
+        self.group_by.push(field.into());
+        self
+    }
+
+    fn aggregate(mut self, kind: AggKind, field: Option<FieldRef>) -> Self {
+        self.aggregates.push(AggSpec { kind, field });
+        self
+    }
+
+    /// number of rows in group
+    pub fn count(self) -> Self {
+        self.aggregate(AggKind::Count, None)
+    }
+
+    pub fn sum<F: Into<FieldRef>>(self, field: F) -> Self {
+        self.aggregate(AggKind::Sum, Some(field.into()))
+    }
+
+    pub fn min<F: Into<FieldRef>>(self, field: F) -> Self {
+        self.aggregate(AggKind::Min, Some(field.into()))
+    }
+
+    pub fn max<F: Into<FieldRef>>(self, field: F) -> Self {
+        self.aggregate(AggKind::Max, Some(field.into()))
+    }
+
+    pub fn avg<F: Into<FieldRef>>(self, field: F) -> Self {
+        self.aggregate(AggKind::Avg, Some(field.into()))
+    }
+
+    /// number of different non nil values of field in group
+    pub fn count_distinct<F: Into<FieldRef>>(self, field: F) -> Self {
+        self.aggregate(AggKind::CountDistinct, Some(field.into()))
+    }
+
+    fn field_no(&self, space_id: u32, field: &FieldRef) -> io::Result<u32> {
+        match field {
+            FieldRef::No(no) => Ok(*no),
+            FieldRef::Name(name) => self.ctx.field_no(space_id, name),
+        }
+    }
+
+    /// scan range and decode result rows as T
+    pub fn run<T: DeserializeOwned>(mut self) -> io::Result<Vec<T>> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        let (space_id, index_id) = self.ctx.get_space_and_index_id(&self.space_name, &self.index_name)?;
+        let key_data = std::mem::take(&mut self.key_data);
+        let rows = self.ctx.index_iterator_data(space_id, index_id, self.iterator_type, key_data)?
+            .with_yield_policy(self.yield_policy)?;
+        match self.filter.take() {
+            Some(filter) => self.fold(space_id, rows.filter_by(filter.bind(self.ctx, space_id)?)),
+            None => self.fold(space_id, rows),
+        }
+    }
+
+    fn fold<T, I>(&self, space_id: u32, rows: I) -> io::Result<Vec<T>>
+        where T: DeserializeOwned,
+              I: Iterator<Item=io::Result<TarantoolTuple<'ctx>>>
+    {
+        let group_fields = self.group_by.iter().map(|field| self.field_no(space_id, field)).collect::<io::Result<Vec<_>>>()?;
+        let agg_fields = self.aggregates.iter()
+            .map(|agg| agg.field.as_ref().map(|field| self.field_no(space_id, field)).transpose())
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let mut groups: Vec<(Vec<u8>, Vec<AggState>)> = Vec::new();
+        let mut group_index: HashMap<Vec<u8>, usize> = HashMap::new();
+        let mut key = Vec::new();
+        let mut normalized_key = Vec::new();
+        if group_fields.is_empty() {
+            groups.push((Vec::new(), self.aggregates.iter().map(|agg| AggState::new(agg.kind)).collect()));
+        }
+        for row in rows {
+            let row = row?;
+            key.clear();
+            normalized_key.clear();
+            for field_no in group_fields.iter() {
+                let field = row.get_field_data(*field_no)?.unwrap_or(&NIL_FIELD);
+                key.extend_from_slice(field);
+                write_normalized(&mut normalized_key, field)?;
+            }
+            let group = match group_index.get(&normalized_key[..]) {
+                Some(group) => *group,
+                None if group_fields.is_empty() => 0,
+                None => {
+                    group_index.insert(normalized_key.clone(), groups.len());
+                    groups.push((key.clone(), self.aggregates.iter().map(|agg| AggState::new(agg.kind)).collect()));
+                    groups.len() - 1
+                }
+            };
+            for (state, field_no) in groups[group].1.iter_mut().zip(agg_fields.iter()) {
+                match field_no {
+                    Some(field_no) => state.add(*field_no, row.get_field_data(*field_no)?.unwrap_or(&NIL_FIELD))?,
+                    None => state.add(0, &NIL_FIELD)?,
+                }
+            }
+        }
+
+        let mut buf = Vec::new();
+        groups.iter()
+            .map(|(key, states)| {
+                buf.clear();
+                write_array_header(&mut buf, (group_fields.len() + states.len()) as u32);
+                buf.extend_from_slice(key);
+                for state in states {
+                    state.write(&mut buf);
+                }
+                decode_serde(&buf[..])
+            })
+            .collect()
+    }
+}
+
+impl TarantoolContext {
+    /// start aggregation over index, see Aggregate
+    pub fn aggregate<S, S1>(&self, space_name: S, index_name: S1) -> Aggregate<'_>
+        where S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        Aggregate::new(self,
+                       String::from_utf8_lossy(space_name.as_ref()).into_owned(),
+                       String::from_utf8_lossy(index_name.as_ref()).into_owned())
+    }
+}
@@ -1,12 +1,14 @@
 extern crate rmp_serde;
 
-use ::std::os::raw::{c_int, c_uchar};
+use ::std::os::raw::{c_char, c_int, c_uchar};
 use rmp_serde::{Deserializer, Serializer};
 
 use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use std::cell::OnceCell;
 use std::error::Error;
 //use std::ffi::CStr;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::io;
 use std::marker::PhantomData;
 use std::mem;
@@ -16,6 +18,32 @@ use std::str::from_utf8_unchecked;
 use crate::tarantool::dict::*;
 use crate::tarantool::internal::*;
 use crate::tarantool::internal::StackValueType;
+use crate::tarantool::msgpack::*;
+pub use crate::tarantool::iter::*;
+pub use crate::tarantool::select::*;
+pub use crate::tarantool::cursor::*;
+pub use crate::tarantool::yielding::YieldPolicy;
+pub use crate::tarantool::keys::*;
+pub use crate::tarantool::schema::*;
+pub use crate::tarantool::ddl::*;
+pub use crate::tarantool::migrations::*;
+pub use crate::tarantool::batch::*;
+pub use crate::tarantool::lookup::*;
+pub use crate::tarantool::encoding::*;
+pub use crate::tarantool::tolerant::*;
+pub use crate::tarantool::dynamic::*;
+pub use crate::tarantool::tuple::*;
+pub use crate::tarantool::keydef::*;
+pub use crate::tarantool::merger::*;
+pub use crate::tarantool::predicate::*;
+pub use crate::tarantool::aggregate::*;
+pub use crate::tarantool::stats::*;
+pub use crate::tarantool::session::*;
+pub use crate::tarantool::ext::{Decimal, Uuid, Datetime, Interval, IntervalAdjust, MP_DECIMAL, MP_UUID, MP_DATETIME, MP_INTERVAL};
+use crate::tarantool::ext::{ExtReader, ExtWriter};
+use crate::tarantool::cursor::ScanKeys;
+use crate::tarantool::yielding::YieldState;
+use crate::tarantool::buffer::{with_serialized, with_serialized_pair};
 
 ///Iterator tarantool type
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -30,8 +58,16 @@ pub enum IteratorType {
     BitsAllSet = 7,
     BitsAnySet = 8,
     BitsAllNotSet = 9,
-    Ovelaps = 10,
-    Neigbor = 11,
+    Overlaps = 10,
+    Neighbor = 11,
+}
+
+#[allow(non_upper_case_globals)]
+impl IteratorType {
+    #[deprecated(note = "misspelled, use IteratorType::Overlaps")]
+    pub const Ovelaps: IteratorType = IteratorType::Overlaps;
+    #[deprecated(note = "misspelled, use IteratorType::Neighbor")]
+    pub const Neigbor: IteratorType = IteratorType::Neighbor;
 }
 
 /// use ZERO bases indexes of args in command or ONE based (operations upsert and update)
@@ -46,6 +82,10 @@ pub enum IndexBase {
     One = 1,
 }
 
+/// Type of field in tuple format or key part
+///
+/// discriminants are numbers of tarantool 1.10 and stay the same for compatibility, types added later follow MAX;
+/// numbers differ between server versions, server_code gives the number of running server
 #[repr(u32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum FieldType {
@@ -59,8 +99,95 @@ pub enum FieldType {
     ARRAY = 7,
     MAP = 8,
     MAX = 9,
+    DOUBLE = 10,
+    VARBINARY = 11,
+    DECIMAL = 12,
+    UUID = 13,
+    DATETIME = 14,
+    INTERVAL = 15,
+}
+
+type ServerVersionFn = unsafe extern "C" fn() -> *const c_char;
+
+lazy_static! {
+    /// major and minor version of running server, None if server doesn't tell it
+    static ref SERVER_VERSION: Option<(u32, u32)> = unsafe {
+        let ptr = libc::dlsym(libc::RTLD_DEFAULT, b"tarantool_version\0".as_ptr() as *const c_char);
+        if ptr.is_null() {
+            None
+        } else {
+            let version = mem::transmute::<*mut libc::c_void, ServerVersionFn>(ptr)();
+            if version.is_null() {
+                None
+            } else {
+                let version = CStr::from_ptr(version).to_string_lossy();
+                let mut parts = version.split(|c: char| !c.is_ascii_digit()).map(|part| part.parse::<u32>().ok());
+                match (parts.next().flatten(), parts.next().flatten()) {
+                    (Some(major), Some(minor)) => Some((major, minor)),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl FieldType {
+    /// field type by its name in space format or index parts
+    pub fn from_name(name: &str) -> Option<FieldType> {
+        match name.to_lowercase().as_str() {
+            "any" | "*" => Some(FieldType::ANY),
+            "unsigned" | "num" => Some(FieldType::UNSIGNED),
+            "string" | "str" => Some(FieldType::STRING),
+            "number" => Some(FieldType::NUMBER),
+            "double" => Some(FieldType::DOUBLE),
+            "integer" => Some(FieldType::INTEGER),
+            "boolean" => Some(FieldType::BOOLEAN),
+            "varbinary" => Some(FieldType::VARBINARY),
+            "scalar" => Some(FieldType::SCALAR),
+            "decimal" => Some(FieldType::DECIMAL),
+            "uuid" => Some(FieldType::UUID),
+            "datetime" => Some(FieldType::DATETIME),
+            "interval" => Some(FieldType::INTERVAL),
+            "array" => Some(FieldType::ARRAY),
+            "map" => Some(FieldType::MAP),
+            _ => None
+        }
+    }
+
+    /// number of type in enum field_type of running server, error if the server has no such type;
+    /// 1.x and 2.x numberings are known, types of 1.10 keep their discriminants when server doesn't tell its version
+    pub fn server_code(self) -> io::Result<u32> {
+        let types: &[FieldType] = match *SERVER_VERSION {
+            Some((1, _)) | Some((2, 0)) | Some((2, 1)) => FIELD_TYPES_1,
+            Some((2, 2)) => FIELD_TYPES_2_2,
+            Some((2, 3)) => FIELD_TYPES_2_3,
+            Some((2, minor)) if minor < 10 => FIELD_TYPES_2_4,
+            Some(version) if version >= (2, 10) => FIELD_TYPES_2_10,
+            _ if (self as u32) < FieldType::MAX as u32 => return Ok(self as u32),
+            _ => &[],
+        };
+        match types.iter().position(|field_type| *field_type == self) {
+            Some(code) => Ok(code as u32),
+            None => Err(make_error(format!("Field type {:?} has no known number on server version {:?}!", self, *SERVER_VERSION), false)),
+        }
+    }
 }
 
+/// enum field_type of servers by version, number of type is its position
+const FIELD_TYPES_1: &[FieldType] = &[FieldType::ANY, FieldType::UNSIGNED, FieldType::STRING, FieldType::NUMBER,
+    FieldType::INTEGER, FieldType::BOOLEAN, FieldType::SCALAR, FieldType::ARRAY, FieldType::MAP];
+const FIELD_TYPES_2_2: &[FieldType] = &[FieldType::ANY, FieldType::UNSIGNED, FieldType::STRING, FieldType::NUMBER,
+    FieldType::INTEGER, FieldType::BOOLEAN, FieldType::VARBINARY, FieldType::SCALAR, FieldType::ARRAY, FieldType::MAP];
+const FIELD_TYPES_2_3: &[FieldType] = &[FieldType::ANY, FieldType::UNSIGNED, FieldType::STRING, FieldType::NUMBER,
+    FieldType::DOUBLE, FieldType::INTEGER, FieldType::BOOLEAN, FieldType::VARBINARY, FieldType::SCALAR, FieldType::DECIMAL,
+    FieldType::ARRAY, FieldType::MAP];
+const FIELD_TYPES_2_4: &[FieldType] = &[FieldType::ANY, FieldType::UNSIGNED, FieldType::STRING, FieldType::NUMBER,
+    FieldType::DOUBLE, FieldType::INTEGER, FieldType::BOOLEAN, FieldType::VARBINARY, FieldType::SCALAR, FieldType::DECIMAL,
+    FieldType::UUID, FieldType::ARRAY, FieldType::MAP];
+const FIELD_TYPES_2_10: &[FieldType] = &[FieldType::ANY, FieldType::UNSIGNED, FieldType::STRING, FieldType::NUMBER,
+    FieldType::DOUBLE, FieldType::INTEGER, FieldType::BOOLEAN, FieldType::VARBINARY, FieldType::SCALAR, FieldType::DECIMAL,
+    FieldType::UUID, FieldType::DATETIME, FieldType::INTERVAL, FieldType::ARRAY, FieldType::MAP];
+
 const SEARCH_SPACE_ID:u32 = 280;
 const SEARCH_INDEX_ID:u32 = 2;
 const SEARCH_PRIMARY_INDEX:u32 = 0;
@@ -106,6 +233,8 @@ pub trait  DecodableOpt <'ctx>{
 #[derive(Debug)]
 pub struct TarantoolTuple<'ctx> {
     pub row_data: *const u8,
+    space_id: Option<u32>,
+    data: OnceCell<Vec<u8>>,
     phantom: PhantomData<&'ctx TarantoolContext>,
 }
 
@@ -113,10 +242,26 @@ impl<'ctx> TarantoolTuple<'ctx> {
     fn new(row_data: *const u8, _ctx: PhantomData<&'ctx TarantoolContext>) -> TarantoolTuple<'ctx> {
         TarantoolTuple {
             row_data,
+            space_id: None,
+            data: OnceCell::new(),
             phantom: PhantomData,
         }
     }
 
+    pub(crate) fn with_space_id(mut self, space_id: u32) -> TarantoolTuple<'ctx> {
+        self.space_id = Some(space_id);
+        self
+    }
+
+    /// space the tuple was read from, None for tuples made in memory or returned from lua
+    pub fn space_id(self: &TarantoolTuple<'ctx>) -> Option<u32> {
+        self.space_id
+    }
+
+    pub(crate) fn from_ptr(row_data: *const u8) -> TarantoolTuple<'ctx> {
+        TarantoolTuple::new(row_data, PhantomData)
+    }
+
     pub fn get_raw_data(self: &TarantoolTuple<'ctx>) -> Vec<u8> {
         unsafe {
             let size = box_tuple_bsize(self.row_data);
@@ -126,6 +271,83 @@ impl<'ctx> TarantoolTuple<'ctx> {
             row_buf
         }
     }
+
+    /// raw msgpack of the whole tuple, copied from the server once and kept with the tuple
+    pub fn get_data(self: &TarantoolTuple<'ctx>) -> &[u8] {
+        self.data.get_or_init(|| self.get_raw_data())
+    }
+
+    /// start and end of fields in tuple data, None for tuple without fields
+    fn fields_range(self: &TarantoolTuple<'ctx>) -> Option<(*const u8, *const u8)> {
+        unsafe {
+            let first_field = box_tuple_field(self.row_data, 0);
+            if first_field.is_null() {
+                return None;
+            }
+            //array header ends right before the first field, only its own bytes are read to find its length
+            let count = box_tuple_field_count(self.row_data);
+            let header_len = if count < 16 && *first_field.sub(1) == 0x90 | count as u8 {
+                1
+            } else if count <= u16::MAX as u32 && *first_field.sub(3) == 0xdc {
+                3
+            } else {
+                5
+            };
+            Some((first_field, first_field.add(box_tuple_bsize(self.row_data) - header_len)))
+        }
+    }
+
+    /// msgpack value starting at pointer into tuple data returned by server, bounded by end of tuple data without copying
+    pub(crate) fn get_data_at(self: &TarantoolTuple<'ctx>, field: *const u8) -> io::Result<&[u8]> {
+        match self.fields_range() {
+            Some((first_field, end)) if field >= first_field && field < end => {
+                let data = unsafe { slice::from_raw_parts(field, end as usize - field as usize) };
+                let len = value_len(data)?;
+                Ok(&data[..len])
+            }
+            _ => Err(make_error("Field pointer is out of tuple data!".to_string(), false)),
+        }
+    }
+
+    /// decode listed fields as one msgpack array, absent fields are decoded as nil
+    pub fn decode_fields<'de, V>(self: &TarantoolTuple<'ctx>, indexes: &[u32]) -> io::Result<V>
+        where V: Deserialize<'de>
+    {
+        let mut buf = Vec::new();
+        self.copy_fields_to_buf(indexes, &mut buf)?;
+        decode_serde(&buf[..])
+    }
+
+    /// copy listed fields into buf as msgpack array, absent fields are written as nil
+    pub fn copy_fields_to_buf(self: &TarantoolTuple<'ctx>, indexes: &[u32], buf: &mut Vec<u8>) -> io::Result<()> {
+        buf.clear();
+        write_array_header(buf, indexes.len() as u32);
+        for index in indexes {
+            match self.get_field_data(*index)? {
+                Some(field) => buf.extend_from_slice(field),
+                None => buf.push(MSGPACK_NIL),
+            }
+        }
+        Ok(())
+    }
+
+    /// raw msgpack of one field, None if tuple is shorter
+    pub fn get_field_data(self: &TarantoolTuple<'ctx>, index: u32) -> io::Result<Option<&[u8]>> {
+        unsafe {
+            let field = box_tuple_field(self.row_data, index as c_int);
+            if field.is_null() {
+                return Ok(None);
+            }
+            //fields are stored one after another, so the next field bounds this one without copying the tuple
+            if index + 1 < box_tuple_field_count(self.row_data) {
+                let next = box_tuple_field(self.row_data, index as c_int + 1);
+                if !next.is_null() && next > field {
+                    return Ok(Some(slice::from_raw_parts(field, next as usize - field as usize)));
+                }
+            }
+            self.get_data_at(field).map(Some)
+        }
+    }
 }
 
 impl<'ctx>  Decodable<'ctx> for TarantoolTuple<'ctx> {
@@ -144,10 +366,9 @@ impl<'ctx>  Decodable<'ctx> for TarantoolTuple<'ctx> {
     fn decode_field<'de, V>(self: &TarantoolTuple<'ctx>, index: u32) -> io::Result<V>
         where V: Deserialize<'de>
     {
-        unsafe {
-            let row_buf = box_tuple_field(self.row_data, index as c_int);
-            let row_buf_slice = slice::from_raw_parts(row_buf, usize::max_value());
-            return decode_serde(row_buf_slice);
+        match self.get_field_data(index)? {
+            Some(field) => decode_serde(field),
+            None => decode_serde(&[MSGPACK_NIL][..]),
         }
     }
 }
@@ -174,11 +395,80 @@ pub struct TarantoolIterator<'ctx> {
     _params: Vec<u8>,
     //store params to keep them in memory
     ctx: PhantomData<&'ctx TarantoolContext>,
+    space_id: u32,
+    index_id: u32,
+    iterator_type: IteratorType,
+    yield_state: Option<Box<YieldState>>,
+    finished: bool,
 }
 
 impl<'ctx> TarantoolIterator<'ctx> {
-    fn new(data: *const u8, params: Vec<u8>, _ctx: &'ctx TarantoolContext) -> TarantoolIterator<'ctx> {
-        TarantoolIterator { data, _params: params, ctx: PhantomData }
+    fn new(data: *const u8, params: Vec<u8>, space_id: u32, index_id: u32, iterator_type: IteratorType, _ctx: &'ctx TarantoolContext) -> TarantoolIterator<'ctx> {
+        TarantoolIterator { data, _params: params, ctx: PhantomData, space_id, index_id, iterator_type, yield_state: None, finished: false }
+    }
+
+    /// yield automatically while iterating
+    ///
+    /// tree iterator survives changes of rows during yield, but server ends it silently when schema was changed
+    /// (during yield or in loop body), so then dictionaries are reloaded and iterator is recreated after
+    /// the last returned row (with its primary key for index which can have equal keys).
+    ///
+    /// BITSET and RTREE iterators can't be repositioned, they only yield
+    pub fn with_yield_policy(mut self, policy: YieldPolicy) -> io::Result<TarantoolIterator<'ctx>> {
+        if policy == YieldPolicy::Never {
+            self.yield_state = None;
+            return Ok(self);
+        }
+        let ctx = TarantoolContext::new_ffi();
+        let keys = match (ctx.index_info_by_id(self.space_id, self.index_id)?.index_type, self.iterator_type) {
+            (IndexType::Rtree, _) | (IndexType::Bitset, _) => None,
+            (_, IteratorType::ALL) | (_, IteratorType::GE) | (_, IteratorType::GT) | (_, IteratorType::EQ) |
+            (_, IteratorType::LE) | (_, IteratorType::LT) | (_, IteratorType::REQ) => Some(ScanKeys::new(&ctx, self.space_id, self.index_id)?),
+            _ => None
+        };
+        let eq_key = match self.iterator_type {
+            IteratorType::EQ | IteratorType::REQ => Some(self._params.clone()),
+            _ => None
+        };
+        self.yield_state = Some(Box::new(YieldState::new(policy, keys, eq_key)));
+        Ok(self)
+    }
+
+    fn yield_and_reposition(self: &mut Self) -> io::Result<()> {
+        let state = match self.yield_state {
+            Some(ref mut state) => state,
+            None => return Ok(())
+        };
+        if state.should_yield() {
+            unsafe {
+                fiber_sleep(0 as f64);
+            }
+            state.reset();
+        }
+        if !state.schema_changed() {
+            return Ok(());
+        }
+        //ids of spaces and indexes in dictionaries may be stale after schema change
+        init_dictionaries()?;
+        if !state.can_reposition() {
+            return Ok(());
+        }
+        //scan starts over if nothing was returned yet
+        let (resume_type, key) = match state.reposition(&TarantoolContext::new_ffi(), self.space_id, self.index_id, self.iterator_type)? {
+            Some(position) => position,
+            None => (self.iterator_type, self._params.clone())
+        };
+        unsafe {
+            let ptr_start = key.as_ptr();
+            let iter = box_index_iterator(self.space_id, self.index_id, resume_type as u8, ptr_start, ptr_start.add(key.len()));
+            if iter as usize == NULL {
+                return make_error_result(format!("error on restore iterator position after schema change! space id={} index id={} ", self.space_id, self.index_id));
+            }
+            box_iterator_free(self.data);
+            self.data = iter;
+            self._params = key;
+        }
+        Ok(())
     }
 }
 
@@ -194,17 +484,42 @@ impl<'ctx> Iterator for TarantoolIterator<'ctx> {
     type Item = io::Result<TarantoolTuple<'ctx>>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        unsafe {
-            let mut ptr_buffer: *mut u8 = mem::uninitialized();
-            let r = box_iterator_next(self.data, &mut ptr_buffer);
-            if r == -1 {
-                return Option::Some(make_error_result(format!("error on receive iterator next value")));
-            }
+        if self.finished {
+            return None;
+        }
+        if let Err(e) = self.yield_and_reposition() {
+            return Some(Err(e));
+        }
+        //rows returned before iterator was repositioned are skipped
+        loop {
+            unsafe {
+                let mut ptr_buffer: *mut u8 = mem::uninitialized();
+                let r = box_iterator_next(self.data, &mut ptr_buffer);
+                if r == -1 {
+                    return Option::Some(make_error_result(format!("error on receive iterator next value")));
+                }
 
-            if ptr_buffer.is_null() {
-                Option::None
-            } else {
-                Option::Some(Ok(TarantoolTuple::new(ptr_buffer, self.ctx)))
+                if ptr_buffer.is_null() {
+                    return Option::None;
+                }
+                let row = TarantoolTuple::new(ptr_buffer, self.ctx).with_space_id(self.space_id);
+                if let Some(ref mut state) = self.yield_state {
+                    match state.passed_last_row(&row) {
+                        Ok(true) => {}
+                        Ok(false) => continue,
+                        Err(e) => return Some(Err(e))
+                    }
+                    match state.eq_key_matches(&row) {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            self.finished = true;
+                            return None;
+                        }
+                        Err(e) => return Some(Err(e))
+                    }
+                    state.row_returned(ptr_buffer);
+                }
+                return Option::Some(Ok(row));
             }
         }
     }
@@ -213,7 +528,7 @@ impl<'ctx> Iterator for TarantoolIterator<'ctx> {
 #[derive(Debug)]
 pub struct LuaCall<'ctx> {
     parameters_count: i32,
-    lua_state: *const c_int,
+    pub(crate) lua_state: *const c_int,
     ctx: PhantomData<&'ctx TarantoolContext>,
 }
 
@@ -227,7 +542,19 @@ impl<'ctx> LuaCall<'ctx> {
         }
     }
 
-    fn increment_param_count(self: &mut Self) {
+    fn from_chunk(_ctx: &'ctx TarantoolContext, code: &str) -> io::Result<LuaCall<'ctx>> {
+        unsafe {
+            let lua_state = luaT_state();
+            if luaL_loadbuffer(lua_state, code.as_ptr(), code.len(), b"=eval\0".as_ptr()) != 0 {
+                let message = lua_tolstring_wrapper(lua_state);
+                lua_pop(lua_state, 1);
+                return Err(make_error(format!("error on load lua chunk! {}", message?), false));
+            }
+            Ok(LuaCall { lua_state, ctx: PhantomData, parameters_count: 0 })
+        }
+    }
+
+    pub(crate) fn increment_param_count(self: &mut Self) {
         self.parameters_count = self.parameters_count + 1;
     }
 
@@ -284,8 +611,10 @@ impl<'ctx> LuaCall<'ctx> {
         unsafe {
             self.increment_param_count();
             let format = box_tuple_format_default();
-            let (ptr_start, ptr_end, _buf) = serialize_to_ptr(value)?;
-            let tuple = box_tuple_new(format, ptr_start, ptr_end);
+            let tuple = with_serialized(value, |data| {
+                let data = data.as_ptr_range();
+                Ok(box_tuple_new(format, data.start, data.end))
+            })?;
             luaT_pushtuple(self.lua_state, tuple);
             Ok(())
         }
@@ -385,7 +714,7 @@ pub struct TarantoolContext {
 }
 
 impl TarantoolContext {
-    fn new_ffi() -> TarantoolContext {
+    pub(crate) fn new_ffi() -> TarantoolContext {
         TarantoolContext { context:NULL as StoredProcCtx,args: NULL as StoredProcArgs,args_end: NULL as StoredProcArgsEnd }
     }
 
@@ -435,6 +764,17 @@ impl TarantoolContext {
     }
 
 
+    /// zero based numbers of tuple fields which make up index key, in key order
+    ///
+    /// error for index with json path parts, they are not whole fields
+    pub fn get_index_key_fields(self: &Self, space_id: u32, index_id: u32) -> io::Result<Vec<u32>> {
+        let info = self.index_info_by_id(space_id, index_id)?;
+        if let Some(part) = info.parts.iter().find(|part| part.path.is_some()) {
+            return Err(make_error(format!("Key fields of index {} can't stand for json path part! field={}", info.name, part.field), false));
+        }
+        Ok(info.key_fields())
+    }
+
     pub fn decode_input_params<'de, T>(self: &Self) -> io::Result<T>
         where T: Deserialize<'de>
     {
@@ -464,6 +804,16 @@ impl TarantoolContext {
         self.index_iterator_raw(space_name, index_name, space_id, index_id, iterator_type, key)
     }
 
+    /// iterator which decodes every row as T
+    pub fn index_iterator_typed<'a, T, SER, S, S1>(self: &'a Self, space_name: S, index_name: S1, iterator_type: IteratorType, key: &SER) -> io::Result<TypedIterator<TarantoolIterator<'a>, T>>
+        where T: DeserializeOwned,
+              SER: Serialize,
+              S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        Ok(self.index_iterator(space_name, index_name, iterator_type, key)?.typed())
+    }
+
     pub fn index_iterator_raw<'a, SER, S, S1>(self: &'a Self,space_name: S, index_name: S1, space_id: u32, index_id: u32, iterator_type: IteratorType, key: &SER) -> io::Result<TarantoolIterator>
         where SER: Serialize,
                 S: AsRef<[u8]>,
@@ -475,7 +825,20 @@ impl TarantoolContext {
             if iter as usize == NULL {
                 return make_error_result(format!("space name={} index name={} ", from_utf8_unchecked(space_name.as_ref()), from_utf8_unchecked(index_name.as_ref())));
             }
-            Ok(TarantoolIterator::new(iter, params, self))
+            Ok(TarantoolIterator::new(iter, params, space_id, index_id, iterator_type, self))
+        }
+    }
+
+    /// iterator by space and index id, key_data is already serialized msgpack array
+    pub fn index_iterator_data<'a>(self: &'a Self, space_id: u32, index_id: u32, iterator_type: IteratorType, key_data: Vec<u8>) -> io::Result<TarantoolIterator<'a>> {
+        unsafe {
+            let ptr_start = key_data.as_ptr();
+            let ptr_end = ptr_start.add(key_data.len());
+            let iter = box_index_iterator(space_id, index_id, iterator_type as u8, ptr_start, ptr_end);
+            if iter as usize == NULL {
+                return make_error_result(format!("error on create iterator! space id={} index id={} ", space_id, index_id));
+            }
+            Ok(TarantoolIterator::new(iter, key_data, space_id, index_id, iterator_type, self))
         }
     }
 
@@ -484,9 +847,11 @@ impl TarantoolContext {
               S: AsRef<[u8]>
     {
         unsafe {
-            let (ptr_start, ptr_end, _params) = serialize_to_ptr(value)?;
             let space_id = self.get_space_id(&space_name)?;
-            let res = box_insert(space_id, ptr_start, ptr_end, ptr::null_mut());
+            let res = with_serialized(value, |data| {
+                let data = data.as_ptr_range();
+                Ok(box_insert(space_id, data.start, data.end, ptr::null_mut()))
+            })?;
             if res == -1 {
                 return make_error_result(format!("error on insert! space name={:?} ", from_utf8_unchecked(space_name.as_ref())));
             }
@@ -499,9 +864,11 @@ impl TarantoolContext {
               S: AsRef<[u8]>
     {
         unsafe {
-            let (ptr_start, ptr_end, _params) = serialize_to_ptr(value)?;
             let space_id = self.get_space_id(&space_name)?;
-            let res = box_replace(space_id, ptr_start, ptr_end, ptr::null_mut());
+            let res = with_serialized(value, |data| {
+                let data = data.as_ptr_range();
+                Ok(box_replace(space_id, data.start, data.end, ptr::null_mut()))
+            })?;
             if res == -1 {
                 return make_error_result(format!("error on replace! space name={:?} ", from_utf8_unchecked(space_name.as_ref())));
             }
@@ -517,9 +884,10 @@ impl TarantoolContext {
         unsafe {
             let (space_id, index_id) = self.get_space_and_index_id(&space_name, &index_name)?;
 
-            let (ptr_start, ptr_end, _params) = serialize_to_ptr(key)?;
-
-            let res = box_delete(space_id, index_id, ptr_start, ptr_end, ptr::null_mut());
+            let res = with_serialized(key, |key| {
+                let key = key.as_ptr_range();
+                Ok(box_delete(space_id, index_id, key.start, key.end, ptr::null_mut()))
+            })?;
             if res == -1 {
                 return make_error_result(format!("error on delete! space name={} index name={} ", from_utf8_unchecked(space_name.as_ref()), from_utf8_unchecked(index_name.as_ref())));
             }
@@ -536,10 +904,10 @@ impl TarantoolContext {
         unsafe {
             let (space_id, index_id) = self.get_space_and_index_id(&space_name, &index_name)?;
 
-            let (key_ptr_start, key_ptr_end, _key_params) = serialize_to_ptr(key)?;
-            let (ops_ptr_start, ops_ptr_end, _ops_params) = serialize_to_ptr(ops)?;
-
-            let res = box_update(space_id, index_id, key_ptr_start, key_ptr_end, ops_ptr_start, ops_ptr_end, index_base as i32, ptr::null_mut());
+            let res = with_serialized_pair(key, ops, |key, ops| {
+                let (key, ops) = (key.as_ptr_range(), ops.as_ptr_range());
+                Ok(box_update(space_id, index_id, key.start, key.end, ops.start, ops.end, index_base as i32, ptr::null_mut()))
+            })?;
             if res == -1 {
                 return make_error_result(format!("error on update! space name={} index name={} ", from_utf8_unchecked(space_name.as_ref()), from_utf8_unchecked(index_name.as_ref())));
             }
@@ -556,10 +924,10 @@ impl TarantoolContext {
         unsafe {
             let (space_id, index_id) = self.get_space_and_index_id(&space_name, &index_name)?;
 
-            let (tuple_ptr_start, tuple_ptr_end, _tuple_params) = serialize_to_ptr(tuple)?;
-            let (ops_ptr_start, ops_ptr_end, _ops_params) = serialize_to_ptr(ops)?;
-
-            let res = box_upsert(space_id, index_id, tuple_ptr_start, tuple_ptr_end, ops_ptr_start, ops_ptr_end, index_base as i32, ptr::null_mut());
+            let res = with_serialized_pair(tuple, ops, |tuple, ops| {
+                let (tuple, ops) = (tuple.as_ptr_range(), ops.as_ptr_range());
+                Ok(box_upsert(space_id, index_id, tuple.start, tuple.end, ops.start, ops.end, index_base as i32, ptr::null_mut()))
+            })?;
             if res == -1 {
                 return make_error_result(format!("error on upsert! space name={} index name={} ", from_utf8_unchecked(space_name.as_ref()), from_utf8_unchecked(index_name.as_ref())));
             }
@@ -604,10 +972,12 @@ impl TarantoolContext {
     {
         unsafe {
 
-            let (key_start, key_end, _params) = serialize_to_ptr(key)?;
             let mut res_tuple: *mut u8 = mem::uninitialized();
 
-            let res = f(space_id, index_id, key_start, key_end, &mut res_tuple);
+            let res = with_serialized(key, |key| {
+                let key = key.as_ptr_range();
+                Ok(f(space_id, index_id, key.start, key.end, &mut res_tuple))
+            })?;
             if res == -1 {
                 return make_error_result(format!("error on get data!"));
             }
@@ -615,7 +985,7 @@ impl TarantoolContext {
                 return Ok(None);
             }
 
-            Ok(Some(TarantoolTuple::new(res_tuple, PhantomData)))
+            Ok(Some(TarantoolTuple::new(res_tuple, PhantomData).with_space_id(space_id)))
         }
     }
 
@@ -672,9 +1042,10 @@ impl TarantoolContext {
         unsafe {
             let (space_id, index_id) = self.get_space_and_index_id(&space_name, &index_name)?;
 
-            let (key_start, key_end, _params) = serialize_to_ptr(key)?;
-
-            let res = box_index_count(space_id, index_id, iterator_type as u8, key_start, key_end);
+            let res = with_serialized(key, |key| {
+                let key = key.as_ptr_range();
+                Ok(box_index_count(space_id, index_id, iterator_type as u8, key.start, key.end))
+            })?;
             if res == -1 {
                 return make_error_result(format!("error on index count! space name={} index name={} ", from_utf8_unchecked(space_name.as_ref()), from_utf8_unchecked(index_name.as_ref())));
             }
@@ -731,23 +1102,42 @@ impl TarantoolContext {
         LuaCall::new(self, fn_name)
     }
 
+    /// call of lua code instead of global function, parameters are available as ... in code
+    ///
+    /// # Examples
+    ///
+    /// let mut call = tarantool.init_eval("return box.session.peer()")?;
+    /// call.call()?;
+    /// let peer = call.pop_str()?;
+    ///
+    pub fn init_eval(&self, code: &str) -> io::Result<LuaCall<'_>> {
+        LuaCall::from_chunk(self, code)
+    }
+
     pub fn return_tuple<'a, SER>(self: &'a Self, result: io::Result<SER>, format: Option<&Vec<FieldType>>) -> c_int
         where SER: Serialize
     {
         match result {
             Ok(ref value) => {
+                let types = match format.map(|fields| fields.iter().map(|field| field.server_code()).collect::<io::Result<Vec<u32>>>()) {
+                    Some(Err(error)) => return self.return_tuple::<SER>(Err(error), None),
+                    Some(Ok(types)) => Some(types),
+                    None => None,
+                };
                 unsafe {
-                    let tuple_format = match format {
+                    let tuple_format = match types {
                         None => box_tuple_format_default(),
-                        Some(fields) => {
-                            let fields_n: Vec<u32> = (0..fields.len()).map(|v| v as u32).collect();
-                            let key_def = box_key_def_new(fields_n.as_ptr(), fields.as_ptr() as *const u32, fields.len() as u32);
+                        Some(types) => {
+                            let fields_n: Vec<u32> = (0..types.len()).map(|v| v as u32).collect();
+                            let key_def = box_key_def_new(fields_n.as_ptr(), types.as_ptr(), types.len() as u32);
                             box_tuple_format_new(&key_def, 1 as u16)
                         }
                     };
 
-                    let (ptr_start, ptr_end, _buf) = serialize_to_ptr(value).unwrap();
-                    let tuple = box_tuple_new(tuple_format, ptr_start, ptr_end);
+                    let tuple = with_serialized(value, |data| {
+                        let data = data.as_ptr_range();
+                        Ok(box_tuple_new(tuple_format, data.start, data.end))
+                    }).unwrap();
                     if tuple as usize == NULL {
                         make_error_result::<String>(format!("error on create tuple!")).unwrap();
                     }
@@ -786,10 +1176,11 @@ pub fn exec_stored_procedure_with_format<F, SER>(
 }
 
 
+/// decode msgpack value read from r, tarantool ext values are decoded by Decimal, Uuid, Datetime and Interval
 pub fn decode_serde<'de, T, R>(r: R) -> io::Result<T>
     where T: Deserialize<'de>, R: io::Read
 {
-    Deserialize::deserialize(&mut Deserializer::new(r)).map_err(map_err_to_io)
+    Deserialize::deserialize(&mut Deserializer::new(ExtReader::new(r))).map_err(|e| make_error(decode_error_message(&e), false))
 }
 
 fn serialize_to_ptr<S: Serialize>(v: &S) -> io::Result<(*const u8, *const u8, Vec<u8>)> {
@@ -802,9 +1193,15 @@ fn serialize_to_ptr<S: Serialize>(v: &S) -> io::Result<(*const u8, *const u8, Ve
     }
 }
 
+/// Decimal, Uuid, Datetime and Interval are written as tarantool ext values
 pub fn serialize_to_buf_mut<W: io::Write, S: Serialize>(wr: &mut W, v: &S) -> io::Result<()> {
 //    v.serialize(&mut Serializer::new(wr).with_struct_map().with_struct_map()).map_err(map_err_to_io)
-    v.serialize(&mut Serializer::new(wr)).map_err(map_err_to_io)
+    v.serialize(&mut Serializer::new(ExtWriter::new(wr))).map_err(map_err_to_io)
+}
+
+/// serialize value to the end of buf
+pub fn serialize_to_vec<S: Serialize>(buf: &mut Vec<u8>, v: &S) -> io::Result<()> {
+    serialize_to_buf_mut(buf, v)
 }
 
 
@@ -817,6 +1214,11 @@ pub fn init_dictionaries()-> io::Result<()> {
         let id: u32 = row.decode_field(0)?;
         let name: String = row.decode_field(2)?;
         add_space_dict_entry(id, name)?;
+        let info = SpaceInfo::from_tuple(&row)?;
+        for (field_no, field) in info.format.iter().enumerate() {
+            add_space_field_dict_entry(id, field_no as u32, field.name.clone())?;
+        }
+        add_space_info_entry(info)?;
     };
 
     for raw_row in tarantool.index_iterator_raw("_index","primary", SEARCH_SPACE_INDEX_ID, SEARCH_PRIMARY_INDEX, IteratorType::ALL, &NO_KEY_SEQ)? {
@@ -825,6 +1227,7 @@ pub fn init_dictionaries()-> io::Result<()> {
         let index_id: u32 = row.decode_field(1)?;
         let index_name: String = row.decode_field(2)?;
         add_space_index_dict_entry(space_id, index_id, index_name)?;
+        add_index_info_entry(IndexInfo::from_tuple(&row, &tarantool)?)?;
     };
     Ok(())
 }
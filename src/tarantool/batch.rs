@@ -0,0 +1,129 @@
+use serde::Serialize;
+use std::io;
+use std::os::raw::c_int;
+use std::ptr;
+use crate::tarantool::api::*;
+use crate::tarantool::internal::*;
+
+/// How batch writes group rows into transactions
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BatchOptions {
+    /// rows per transaction
+    pub chunk_size: usize,
+    /// give control to other fibers after every committed chunk
+    pub yield_between_chunks: bool,
+    /// roll back current chunk and stop on first failed row, earlier chunks stay committed
+    pub stop_on_error: bool,
+}
+
+impl Default for BatchOptions {
+    fn default() -> BatchOptions {
+        BatchOptions { chunk_size: 1000, yield_between_chunks: true, stop_on_error: false }
+    }
+}
+
+/// Failed row of batch, row is zero based position in input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RowError {
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BatchResult {
+    /// rows written and committed
+    pub written: usize,
+    pub failed: Vec<RowError>,
+}
+
+impl TarantoolContext {
+    /// write serialized rows by chunks, rows join caller's transaction if it is active
+    fn write_batch<I, F>(&self, rows: I, options: &BatchOptions, mut write: F) -> io::Result<BatchResult>
+        where I: IntoIterator,
+              I::Item: Serialize,
+              F: FnMut(*const u8, *const u8) -> c_int
+    {
+        let chunk_size = options.chunk_size.max(1);
+        let own_txn = self.txn_id() < 0;
+        let mut result = BatchResult::default();
+        let mut buf = Vec::new();
+        let mut in_chunk = 0;
+        let mut written_in_chunk = 0;
+        for (row_no, row) in rows.into_iter().enumerate() {
+            if own_txn && in_chunk == 0 {
+                self.txn_begin()?;
+            }
+            buf.clear();
+            let res = match serialize_to_buf_mut(&mut buf, &row) {
+                Ok(_) => {
+                    let ptr_start = buf.as_ptr();
+                    match write(ptr_start, unsafe { ptr_start.add(buf.len()) }) {
+                        -1 => Err(last_box_error_message().unwrap_or_else(|| "unknown error".to_string())),
+                        _ => Ok(()),
+                    }
+                }
+                Err(e) => Err(e.to_string()),
+            };
+            match res {
+                Ok(_) => written_in_chunk += 1,
+                Err(message) if options.stop_on_error => {
+                    if own_txn {
+                        self.txn_rollback()?;
+                    }
+                    return Err(make_error(format!("Batch stopped at row {}, {} rows committed! {}", row_no, result.written, message), false));
+                }
+                Err(message) => result.failed.push(RowError { row: row_no, message }),
+            }
+            in_chunk += 1;
+            if own_txn && in_chunk >= chunk_size {
+                self.txn_commit()?;
+                result.written += written_in_chunk;
+                in_chunk = 0;
+                written_in_chunk = 0;
+                if options.yield_between_chunks {
+                    self.fiber_yield();
+                }
+            }
+        }
+        if own_txn && in_chunk > 0 {
+            self.txn_commit()?;
+        }
+        result.written += written_in_chunk;
+        Ok(result)
+    }
+
+    /// insert rows in transactions of options.chunk_size rows, duplicates and other failed rows are reported in result
+    ///
+    /// # Examples
+    ///
+    /// let res = tarantool.insert_many(COUNTRY_SPACE, countries.iter(), &BatchOptions::default())?;
+    ///
+    pub fn insert_many<I, S>(&self, space_name: S, rows: I, options: &BatchOptions) -> io::Result<BatchResult>
+        where I: IntoIterator,
+              I::Item: Serialize,
+              S: AsRef<[u8]>
+    {
+        let space_id = self.get_space_id(&space_name)?;
+        self.write_batch(rows, options, |start, end| unsafe { box_insert(space_id, start, end, ptr::null_mut()) })
+    }
+
+    pub fn replace_many<I, S>(&self, space_name: S, rows: I, options: &BatchOptions) -> io::Result<BatchResult>
+        where I: IntoIterator,
+              I::Item: Serialize,
+              S: AsRef<[u8]>
+    {
+        let space_id = self.get_space_id(&space_name)?;
+        self.write_batch(rows, options, |start, end| unsafe { box_replace(space_id, start, end, ptr::null_mut()) })
+    }
+
+    /// delete rows by keys, absent keys are not errors
+    pub fn delete_many<I, S, S1>(&self, space_name: S, index_name: S1, keys: I, options: &BatchOptions) -> io::Result<BatchResult>
+        where I: IntoIterator,
+              I::Item: Serialize,
+              S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        let (space_id, index_id) = self.get_space_and_index_id(&space_name, &index_name)?;
+        self.write_batch(keys, options, |start, end| unsafe { box_delete(space_id, index_id, start, end, ptr::null_mut()) })
+    }
+}
@@ -0,0 +1,57 @@
+use serde::Serialize;
+use std::cell::RefCell;
+use std::io;
+use crate::tarantool::api::serialize_to_buf_mut;
+
+const INITIAL_CAPACITY: usize = 1024;
+/// buffers grown by huge values are not kept
+const MAX_RETAINED_CAPACITY: usize = 1024 * 1024;
+
+thread_local! {
+    /// all fibers of tx thread share it, box call can yield (on WAL write etc.) while buffer is borrowed,
+    /// other fibers can't borrow it then and serialize into new Vec
+    static SERIALIZE_BUFFER: RefCell<Vec<u8>> = RefCell::new(Vec::with_capacity(INITIAL_CAPACITY));
+}
+
+/// run f with reusable buffer, falls back to new Vec if buffer is in use by outer call (lua triggers etc.) or by yielded fiber
+fn with_buffer<F, R>(f: F) -> io::Result<R>
+    where F: FnOnce(&mut Vec<u8>) -> io::Result<R>
+{
+    SERIALIZE_BUFFER.with(|cell| match cell.try_borrow_mut() {
+        Ok(mut buf) => {
+            buf.clear();
+            let res = f(&mut buf);
+            if buf.capacity() > MAX_RETAINED_CAPACITY {
+                *buf = Vec::with_capacity(INITIAL_CAPACITY);
+            }
+            res
+        }
+        Err(_) => f(&mut Vec::new()),
+    })
+}
+
+/// serialize value into reusable buffer and pass msgpack to f, data must not be used after f returns
+pub(crate) fn with_serialized<S, F, R>(value: &S, f: F) -> io::Result<R>
+    where S: Serialize,
+          F: FnOnce(&[u8]) -> io::Result<R>
+{
+    with_buffer(|buf| {
+        serialize_to_buf_mut(buf, value)?;
+        f(&buf[..])
+    })
+}
+
+/// same as with_serialized for two values like key and update ops
+pub(crate) fn with_serialized_pair<S, S1, F, R>(first: &S, second: &S1, f: F) -> io::Result<R>
+    where S: Serialize,
+          S1: Serialize,
+          F: FnOnce(&[u8], &[u8]) -> io::Result<R>
+{
+    with_buffer(|buf| {
+        serialize_to_buf_mut(buf, first)?;
+        let first_len = buf.len();
+        serialize_to_buf_mut(buf, second)?;
+        let (first, second) = buf.split_at(first_len);
+        f(first, second)
+    })
+}
@@ -0,0 +1,337 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::io;
+use crate::tarantool::api::*;
+use crate::tarantool::internal::*;
+use crate::tarantool::keydef::KeyDef;
+use crate::tarantool::msgpack::*;
+
+const CURSOR_FORMAT_VERSION: u8 = 2;
+const CURSOR_FIELDS_COUNT: u32 = 7;
+const PRIMARY_INDEX_ID: u32 = 0;
+
+/// Position of scan over index, can be passed to client as opaque token and used to resume scan on next call
+///
+/// token is base64 of msgpack array [format version, schema version, space id, index id, iterator type, last key, last primary key]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    pub space_id: u32,
+    pub index_id: u32,
+    pub iterator_type: IteratorType,
+    pub schema_version: u64,
+    /// key of last returned row, msgpack array
+    pub last_key: Vec<u8>,
+    /// primary key of last returned row for index which can have equal keys, msgpack array
+    pub last_primary_key: Option<Vec<u8>>,
+}
+
+fn invalid_token<T>(reason: &str) -> io::Result<T> {
+    Err(make_error(format!("Invalid cursor token! {}", reason), false))
+}
+
+fn iterator_type_from_u8(value: u8) -> Option<IteratorType> {
+    match value {
+        0 => Some(IteratorType::EQ),
+        1 => Some(IteratorType::REQ),
+        2 => Some(IteratorType::ALL),
+        3 => Some(IteratorType::LT),
+        4 => Some(IteratorType::LE),
+        5 => Some(IteratorType::GE),
+        6 => Some(IteratorType::GT),
+        _ => None,
+    }
+}
+
+fn read_token_int(data: &mut &[u8]) -> io::Result<u64> {
+    rmp::decode::read_int(data).or_else(|e| invalid_token(&format!("{:?}", e)))
+}
+
+fn read_token_key<'a>(data: &mut &'a [u8]) -> io::Result<&'a [u8]> {
+    let key_len = value_len(data)?;
+    read_array_header(data)?;
+    let (key, rest) = data.split_at(key_len);
+    *data = rest;
+    Ok(key)
+}
+
+pub(crate) fn is_reverse(iterator_type: IteratorType) -> bool {
+    matches!(iterator_type, IteratorType::LT | IteratorType::LE | IteratorType::REQ)
+}
+
+/// Fields which give position of row in index
+///
+/// rows with equal keys in index which isn't unique (or has nullable parts) are ordered by primary key,
+/// so position in such index needs primary key fields too
+#[derive(Debug)]
+pub(crate) struct ScanKeys {
+    key_fields: Vec<u32>,
+    primary_fields: Option<Vec<u32>>,
+}
+
+impl ScanKeys {
+    pub(crate) fn new(ctx: &TarantoolContext, space_id: u32, index_id: u32) -> io::Result<ScanKeys> {
+        let info = ctx.index_info_by_id(space_id, index_id)?;
+        let key_fields = ctx.get_index_key_fields(space_id, index_id)?;
+        let primary_fields = if info.unique && !info.parts.iter().any(|part| part.is_nullable) {
+            None
+        } else {
+            Some(ctx.get_index_key_fields(space_id, PRIMARY_INDEX_ID)?)
+        };
+        Ok(ScanKeys { key_fields, primary_fields })
+    }
+
+    /// copy index key and, if needed, primary key of row
+    pub(crate) fn copy_keys(&self, row: &TarantoolTuple, key: &mut Vec<u8>, primary_key: &mut Option<Vec<u8>>) -> io::Result<()> {
+        row.copy_fields_to_buf(&self.key_fields, key)?;
+        match self.primary_fields {
+            Some(ref primary_fields) => row.copy_fields_to_buf(primary_fields, primary_key.get_or_insert_with(Vec::new)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Skips rows which were already returned when scan is restarted with GE/LE from key which several rows can have
+#[derive(Debug)]
+pub(crate) struct ResumeFilter {
+    key_def: KeyDef,
+    primary_key_def: KeyDef,
+    last_key: Vec<u8>,
+    last_primary_key: Vec<u8>,
+    reverse: bool,
+}
+
+impl ResumeFilter {
+    pub(crate) fn new(ctx: &TarantoolContext, space_id: u32, index_id: u32, reverse: bool, last_key: Vec<u8>, last_primary_key: Vec<u8>) -> io::Result<ResumeFilter> {
+        let key_def = KeyDef::new(&ctx.index_info_by_id(space_id, index_id)?.parts)?;
+        let primary_key_def = KeyDef::new(&ctx.index_info_by_id(space_id, PRIMARY_INDEX_ID)?.parts)?;
+        Ok(ResumeFilter { key_def, primary_key_def, last_key, last_primary_key, reverse })
+    }
+
+    /// true if row goes after last returned row in scan order, all following rows go after it too
+    pub(crate) fn passed(&self, row: &TarantoolTuple) -> io::Result<bool> {
+        if self.key_def.compare_with_key_data(row, &self.last_key)? != Ordering::Equal {
+            return Ok(true);
+        }
+        let order = self.primary_key_def.compare_with_key_data(row, &self.last_primary_key)?;
+        Ok(order == if self.reverse { Ordering::Less } else { Ordering::Greater })
+    }
+}
+
+/// iterator type and filter to continue scan after row with given keys
+pub(crate) fn resume_after(ctx: &TarantoolContext, space_id: u32, index_id: u32, reverse: bool, last_key: &[u8], last_primary_key: Option<&[u8]>) -> io::Result<(IteratorType, Option<ResumeFilter>)> {
+    match (last_primary_key, reverse) {
+        (None, false) => Ok((IteratorType::GT, None)),
+        (None, true) => Ok((IteratorType::LT, None)),
+        (Some(last_primary_key), _) => {
+            let filter = ResumeFilter::new(ctx, space_id, index_id, reverse, last_key.to_vec(), last_primary_key.to_vec())?;
+            Ok((if reverse { IteratorType::LE } else { IteratorType::GE }, Some(filter)))
+        }
+    }
+}
+
+impl Cursor {
+    /// iterator type to continue scan after last key, rows with equal key are skipped by primary key then
+    pub fn resume_iterator_type(&self) -> IteratorType {
+        match (is_reverse(self.iterator_type), self.last_primary_key.is_some()) {
+            (false, false) => IteratorType::GT,
+            (false, true) => IteratorType::GE,
+            (true, false) => IteratorType::LT,
+            (true, true) => IteratorType::LE,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let mut buf = Vec::with_capacity(self.last_key.len() + self.last_primary_key.as_ref().map_or(1, |v| v.len()) + 24);
+        write_array_header(&mut buf, CURSOR_FIELDS_COUNT);
+        //writing into Vec can't fail
+        let _ = rmp::encode::write_uint(&mut buf, CURSOR_FORMAT_VERSION as u64);
+        let _ = rmp::encode::write_uint(&mut buf, self.schema_version);
+        let _ = rmp::encode::write_uint(&mut buf, self.space_id as u64);
+        let _ = rmp::encode::write_uint(&mut buf, self.index_id as u64);
+        let _ = rmp::encode::write_uint(&mut buf, self.iterator_type as u64);
+        buf.extend_from_slice(&self.last_key);
+        match self.last_primary_key {
+            Some(ref last_primary_key) => buf.extend_from_slice(last_primary_key),
+            None => buf.push(MSGPACK_NIL),
+        }
+        base64::encode_mode(&buf, base64::Base64Mode::UrlSafe)
+    }
+
+    pub fn decode(token: &str) -> io::Result<Cursor> {
+        let buf = match base64::decode_mode(token, base64::Base64Mode::UrlSafe) {
+            Ok(buf) => buf,
+            Err(e) => return invalid_token(&format!("{:?}", e)),
+        };
+        let (count, header_len) = read_array_header(&buf)?;
+        if count != CURSOR_FIELDS_COUNT {
+            return invalid_token("unexpected fields count");
+        }
+        let mut data = &buf[header_len..];
+        if read_token_int(&mut data)? != CURSOR_FORMAT_VERSION as u64 {
+            return invalid_token("unsupported format version");
+        }
+        let schema_version = read_token_int(&mut data)?;
+        let space_id = read_token_int(&mut data)? as u32;
+        let index_id = read_token_int(&mut data)? as u32;
+        let iterator_type = match iterator_type_from_u8(read_token_int(&mut data)? as u8) {
+            Some(iterator_type) => iterator_type,
+            None => return invalid_token("unsupported iterator type"),
+        };
+        let last_key = read_token_key(&mut data)?.to_vec();
+        let last_primary_key = match data.first() {
+            Some(&MSGPACK_NIL) => {
+                data = &data[1..];
+                None
+            }
+            _ => Some(read_token_key(&mut data)?.to_vec()),
+        };
+        if !data.is_empty() {
+            return invalid_token("trailing data");
+        }
+        Ok(Cursor { space_id, index_id, iterator_type, schema_version, last_key, last_primary_key })
+    }
+}
+
+/// Iterator which remembers key of last returned row, see TarantoolContext::cursor_iterator
+#[derive(Debug)]
+pub struct CursorIterator<'ctx> {
+    inner: TarantoolIterator<'ctx>,
+    space_id: u32,
+    index_id: u32,
+    iterator_type: IteratorType,
+    schema_version: u64,
+    keys: ScanKeys,
+    last_key: Option<Vec<u8>>,
+    last_primary_key: Option<Vec<u8>>,
+    resume: Option<ResumeFilter>,
+}
+
+impl<'ctx> CursorIterator<'ctx> {
+    /// cursor positioned after last returned row, None if nothing was returned yet
+    pub fn cursor(&self) -> Option<Cursor> {
+        self.last_key.as_ref().map(|last_key| Cursor {
+            space_id: self.space_id,
+            index_id: self.index_id,
+            iterator_type: self.iterator_type,
+            schema_version: self.schema_version,
+            last_key: last_key.clone(),
+            last_primary_key: self.last_primary_key.clone(),
+        })
+    }
+
+    pub fn token(&self) -> Option<String> {
+        self.cursor().map(|cursor| cursor.encode())
+    }
+}
+
+impl<'ctx> Iterator for CursorIterator<'ctx> {
+    type Item = io::Result<TarantoolTuple<'ctx>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let row = match self.inner.next()? {
+                Ok(row) => row,
+                Err(e) => return Some(Err(e)),
+            };
+            if let Some(ref resume) = self.resume {
+                match resume.passed(&row) {
+                    Ok(true) => self.resume = None,
+                    Ok(false) => continue,
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            let last_key = self.last_key.get_or_insert_with(Vec::new);
+            return match self.keys.copy_keys(&row, last_key, &mut self.last_primary_key) {
+                Ok(_) => Some(Ok(row)),
+                Err(e) => Some(Err(e)),
+            };
+        }
+    }
+}
+
+/// One page of scan, cursor is None when scan is finished
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub rows: Vec<T>,
+    pub cursor: Option<String>,
+}
+
+impl TarantoolContext {
+    pub fn schema_version(&self) -> u64 {
+        unsafe {
+            box_schema_version()
+        }
+    }
+
+    /// scan index from key, or from position saved in token if it is given
+    ///
+    /// only ALL, GE, GT, LE and LT iterators can be resumed, token is rejected if schema was changed or
+    /// if it was made for other space or index
+    ///
+    /// token of index which isn't unique keeps primary key of last row too, so rows with equal keys aren't lost between pages
+    pub fn cursor_iterator<SER, S, S1>(&self, space_name: S, index_name: S1, iterator_type: IteratorType, key: &SER, token: Option<&str>) -> io::Result<CursorIterator<'_>>
+        where SER: Serialize,
+              S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        match iterator_type {
+            IteratorType::ALL | IteratorType::GE | IteratorType::GT | IteratorType::LE | IteratorType::LT => {}
+            _ => return make_error_result(format!("iterator type {:?} is not supported by cursors!", iterator_type)),
+        }
+        let (space_id, index_id) = self.get_space_and_index_id(&space_name, &index_name)?;
+        let schema_version = self.schema_version();
+
+        let keys = ScanKeys::new(self, space_id, index_id)?;
+        let (start_type, key_data, resume) = match token {
+            None => {
+                let mut key_data = Vec::new();
+                serialize_to_buf_mut(&mut key_data, key)?;
+                (iterator_type, key_data, None)
+            }
+            Some(token) => {
+                let cursor = Cursor::decode(token)?;
+                if cursor.space_id != space_id || cursor.index_id != index_id || cursor.iterator_type != iterator_type {
+                    return invalid_token("token was made for other scan");
+                }
+                if cursor.schema_version != schema_version {
+                    return invalid_token("schema was changed");
+                }
+                if cursor.last_primary_key.is_some() != keys.primary_fields.is_some() {
+                    return invalid_token("token was made for other index");
+                }
+                let (start_type, resume) = resume_after(self, space_id, index_id, is_reverse(iterator_type), &cursor.last_key, cursor.last_primary_key.as_deref())?;
+                (start_type, cursor.last_key, resume)
+            }
+        };
+
+        let inner = self.index_iterator_data(space_id, index_id, start_type, key_data)?;
+        Ok(CursorIterator { inner, space_id, index_id, iterator_type, schema_version, keys, last_key: None, last_primary_key: None, resume })
+    }
+
+    /// read up to limit rows starting from token, returned page contains token for next call
+    pub fn scan_page<T, SER, S, S1>(&self, space_name: S, index_name: S1, iterator_type: IteratorType, key: &SER, token: Option<&str>, limit: usize) -> io::Result<Page<T>>
+        where T: DeserializeOwned,
+              SER: Serialize,
+              S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        let mut iter = self.cursor_iterator(space_name, index_name, iterator_type, key, token)?;
+        let mut rows = Vec::with_capacity(limit);
+        while rows.len() < limit {
+            match iter.next() {
+                Some(row) => rows.push(row?.decode()?),
+                None => return Ok(Page { rows, cursor: None }),
+            }
+        }
+        let cursor = iter.token();
+        //look ahead to not return cursor for empty page
+        match iter.next() {
+            Some(row) => {
+                row?;
+                Ok(Page { rows, cursor })
+            }
+            None => Ok(Page { rows, cursor: None }),
+        }
+    }
+}
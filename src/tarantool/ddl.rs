@@ -0,0 +1,239 @@
+use rmpv::Value;
+use std::io;
+use crate::tarantool::api::*;
+
+/// DDL goes through box.schema lua api, chunks are run by global dostring with arguments packed in one tuple
+const CREATE_SPACE_LUA: &str = "
+    local args = ...
+    box.schema.create_space(args[1], args[2])
+";
+
+const DROP_SPACE_LUA: &str = "
+    local args = ...
+    local space = box.space[args[1]]
+    if space ~= nil then
+        space:drop()
+    elseif not args[2] then
+        error(string.format('Space %s does not exist', args[1]))
+    end
+";
+
+const RENAME_SPACE_LUA: &str = "
+    local args = ...
+    local space = box.space[args[1]]
+    if space == nil then
+        error(string.format('Space %s does not exist', args[1]))
+    end
+    space:rename(args[2])
+";
+
+const FORMAT_SPACE_LUA: &str = "
+    local args = ...
+    local space = box.space[args[1]]
+    if space == nil then
+        error(string.format('Space %s does not exist', args[1]))
+    end
+    space:format(args[2])
+";
+
+const CREATE_INDEX_LUA: &str = "
+    local args = ...
+    local space = box.space[args[1]]
+    if space == nil then
+        error(string.format('Space %s does not exist', args[1]))
+    end
+    space:create_index(args[2], args[3])
+";
+
+const DROP_INDEX_LUA: &str = "
+    local args = ...
+    local space = box.space[args[1]]
+    if space == nil then
+        error(string.format('Space %s does not exist', args[1]))
+    end
+    local index = space.index[args[2]]
+    if index ~= nil then
+        index:drop()
+    elseif not args[3] then
+        error(string.format('Index %s does not exist in space %s', args[2], args[1]))
+    end
+";
+
+const RENAME_INDEX_LUA: &str = "
+    local args = ...
+    local space = box.space[args[1]]
+    if space == nil then
+        error(string.format('Space %s does not exist', args[1]))
+    end
+    local index = space.index[args[2]]
+    if index == nil then
+        error(string.format('Index %s does not exist in space %s', args[2], args[1]))
+    end
+    index:rename(args[3])
+";
+
+/// Options of new space, see box.schema.create_space
+///
+/// # Examples
+///
+/// tarantool.create_space("users", &SpaceOptions {
+///     format: vec![FieldInfo::new("id", "unsigned"), FieldInfo::new("name", "string")],
+///     if_not_exists: true,
+///     ..Default::default()
+/// })?;
+///
+#[derive(Debug, Clone, Default)]
+pub struct SpaceOptions {
+    /// memtx if not set
+    pub engine: Option<String>,
+    pub format: Vec<FieldInfo>,
+    pub id: Option<u32>,
+    pub field_count: Option<u32>,
+    pub temporary: bool,
+    pub if_not_exists: bool,
+}
+
+/// Options of new index, see space:create_index
+#[derive(Debug, Clone)]
+pub struct IndexOptions {
+    pub index_type: IndexType,
+    pub unique: bool,
+    pub parts: Vec<IndexPart>,
+    pub if_not_exists: bool,
+}
+
+impl IndexOptions {
+    /// unique index
+    pub fn new(index_type: IndexType, parts: Vec<IndexPart>) -> IndexOptions {
+        IndexOptions { index_type, unique: true, parts, if_not_exists: false }
+    }
+}
+
+fn name_value<S>(name: S) -> Value where S: AsRef<[u8]> {
+    Value::from(String::from_utf8_lossy(name.as_ref()).into_owned())
+}
+
+fn entry<V>(key: &str, value: V) -> (Value, Value) where V: Into<Value> {
+    (Value::from(key), value.into())
+}
+
+fn format_value(format: &[FieldInfo]) -> Value {
+    Value::Array(format.iter()
+        .map(|field| Value::Map(vec![
+            entry("name", field.name.as_str()),
+            entry("type", field.type_name.as_str()),
+            entry("is_nullable", field.is_nullable),
+        ]))
+        .collect())
+}
+
+impl SpaceOptions {
+    fn to_value(&self) -> Value {
+        let mut opts = vec![
+            entry("temporary", self.temporary),
+            entry("if_not_exists", self.if_not_exists),
+        ];
+        if let Some(ref engine) = self.engine {
+            opts.push(entry("engine", engine.as_str()));
+        }
+        if !self.format.is_empty() {
+            opts.push(entry("format", format_value(&self.format)));
+        }
+        if let Some(id) = self.id {
+            opts.push(entry("id", id));
+        }
+        if let Some(field_count) = self.field_count {
+            opts.push(entry("field_count", field_count));
+        }
+        Value::Map(opts)
+    }
+}
+
+impl IndexOptions {
+    fn to_value(&self) -> Value {
+        let parts = self.parts.iter()
+            .map(|part| {
+                //fields are 1 based in lua
+                let mut opts = vec![
+                    entry("field", part.field + 1),
+                    entry("type", part.type_name.as_str()),
+                    entry("is_nullable", part.is_nullable),
+                ];
+                if let Some(ref collation) = part.collation {
+                    opts.push(entry("collation", collation.as_str()));
+                }
+                if let Some(ref path) = part.path {
+                    opts.push(entry("path", path.as_str()));
+                }
+                Value::Map(opts)
+            })
+            .collect();
+        Value::Map(vec![
+            entry("type", self.index_type.name()),
+            entry("unique", self.unique),
+            entry("parts", Value::Array(parts)),
+            entry("if_not_exists", self.if_not_exists),
+        ])
+    }
+}
+
+impl TarantoolContext {
+    /// run DDL chunk and reload space and index dictionaries
+    fn run_ddl(&self, code: &str, args: &[Value]) -> io::Result<()> {
+        let mut call = self.init_call("dostring")?;
+        call.push_str(code);
+        call.push_tuple(&args)?;
+        call.call()?;
+        init_dictionaries()
+    }
+
+    pub fn create_space<S>(&self, space_name: S, options: &SpaceOptions) -> io::Result<()>
+        where S: AsRef<[u8]>
+    {
+        self.run_ddl(CREATE_SPACE_LUA, &[name_value(space_name), options.to_value()])
+    }
+
+    /// drop space with all its data, missing space is not an error if if_exists is set
+    pub fn drop_space<S>(&self, space_name: S, if_exists: bool) -> io::Result<()>
+        where S: AsRef<[u8]>
+    {
+        self.run_ddl(DROP_SPACE_LUA, &[name_value(space_name), Value::from(if_exists)])
+    }
+
+    pub fn rename_space<S, S1>(&self, space_name: S, new_name: S1) -> io::Result<()>
+        where S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        self.run_ddl(RENAME_SPACE_LUA, &[name_value(space_name), name_value(new_name)])
+    }
+
+    /// replace space format, existing tuples must match new format
+    pub fn alter_space_format<S>(&self, space_name: S, format: &[FieldInfo]) -> io::Result<()>
+        where S: AsRef<[u8]>
+    {
+        self.run_ddl(FORMAT_SPACE_LUA, &[name_value(space_name), format_value(format)])
+    }
+
+    pub fn create_index<S, S1>(&self, space_name: S, index_name: S1, options: &IndexOptions) -> io::Result<()>
+        where S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        self.run_ddl(CREATE_INDEX_LUA, &[name_value(space_name), name_value(index_name), options.to_value()])
+    }
+
+    /// missing index is not an error if if_exists is set
+    pub fn drop_index<S, S1>(&self, space_name: S, index_name: S1, if_exists: bool) -> io::Result<()>
+        where S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        self.run_ddl(DROP_INDEX_LUA, &[name_value(space_name), name_value(index_name), Value::from(if_exists)])
+    }
+
+    pub fn rename_index<S, S1, S2>(&self, space_name: S, index_name: S1, new_name: S2) -> io::Result<()>
+        where S: AsRef<[u8]>,
+              S1: AsRef<[u8]>,
+              S2: AsRef<[u8]>
+    {
+        self.run_ddl(RENAME_INDEX_LUA, &[name_value(space_name), name_value(index_name), name_value(new_name)])
+    }
+}
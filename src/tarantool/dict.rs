@@ -1,16 +1,23 @@
 use std::sync::RwLock;
 use std::collections::HashMap;
 use std::io;
+use crate::tarantool::schema::{SpaceInfo, IndexInfo};
 
 
 lazy_static! {
     pub static ref SPACE_DICTIONARY: RwLock<HashMap<String,u32>> = RwLock::new(HashMap::new());
     pub static ref SPACE_INDEX_DICTIONARY: RwLock<HashMap<u32,HashMap<String,u32>>> = RwLock::new(HashMap::new());
+    pub static ref SPACE_FIELD_DICTIONARY: RwLock<HashMap<u32,HashMap<String,u32>>> = RwLock::new(HashMap::new());
+    pub static ref SPACE_INFO_DICTIONARY: RwLock<HashMap<u32,SpaceInfo>> = RwLock::new(HashMap::new());
+    pub static ref SPACE_INDEXES_DICTIONARY: RwLock<HashMap<u32,Vec<IndexInfo>>> = RwLock::new(HashMap::new());
 }
 
 pub fn clear_dictionaries()  {
     SPACE_DICTIONARY.write().unwrap().clear();
     SPACE_INDEX_DICTIONARY.write().unwrap().clear();
+    SPACE_FIELD_DICTIONARY.write().unwrap().clear();
+    SPACE_INFO_DICTIONARY.write().unwrap().clear();
+    SPACE_INDEXES_DICTIONARY.write().unwrap().clear();
 }
 
 pub fn add_space_dict_entry(space_id:u32, name:String) -> io::Result<()> {
@@ -24,6 +31,23 @@ pub fn add_space_index_dict_entry(space_id:u32, index_id:u32, name:String) -> io
     Ok(())
 }
 
+pub fn add_space_field_dict_entry(space_id:u32, field_no:u32, name:String) -> io::Result<()> {
+    let mut data = SPACE_FIELD_DICTIONARY.write().unwrap();
+    data.entry(space_id).or_default().insert(name, field_no);
+    Ok(())
+}
+
+pub fn add_space_info_entry(info:SpaceInfo) -> io::Result<()> {
+    SPACE_INFO_DICTIONARY.write().unwrap().insert(info.id, info);
+    Ok(())
+}
+
+pub fn add_index_info_entry(info:IndexInfo) -> io::Result<()> {
+    let mut data = SPACE_INDEXES_DICTIONARY.write().unwrap();
+    data.entry(info.space_id).or_default().push(info);
+    Ok(())
+}
+
 pub fn search_space_id(name:&str) -> Option<u32> {
     return SPACE_DICTIONARY.read().unwrap().get(name).map(|v|*v)
 }
@@ -35,3 +59,17 @@ pub fn search_index_id(space_id:u32, index_name:&str) -> Option<u32> {
         .map(|v|*v)
 }
 
+pub fn search_field_no(space_id:u32, field_name:&str) -> Option<u32> {
+    return SPACE_FIELD_DICTIONARY.read().unwrap()
+        .get(&space_id)
+        .and_then(|v|v.get(field_name))
+        .copied()
+}
+
+pub fn search_space_info(space_id:u32) -> Option<SpaceInfo> {
+    return SPACE_INFO_DICTIONARY.read().unwrap().get(&space_id).cloned()
+}
+
+pub fn search_space_indexes(space_id:u32) -> Option<Vec<IndexInfo>> {
+    return SPACE_INDEXES_DICTIONARY.read().unwrap().get(&space_id).cloned()
+}
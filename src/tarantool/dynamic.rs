@@ -0,0 +1,388 @@
+use rmpv::Value;
+use serde::de::{Deserialize, DeserializeOwned, Deserializer};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use serde_json::Value as JsonValue;
+use std::cmp::Ordering;
+use std::io;
+use std::ops::Index;
+use std::slice;
+use crate::tarantool::api::*;
+use crate::tarantool::ext::{ext_from_bin, serialize_raw_ext, ExtValue};
+use crate::tarantool::internal::*;
+
+/// Kind of msgpack value, ext values of tarantool are told apart by ext type
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    Nil,
+    Boolean,
+    Integer,
+    Float,
+    String,
+    Binary,
+    Array,
+    Map,
+    Ext(i8),
+}
+
+impl ValueKind {
+    pub fn of(value: &Value) -> ValueKind {
+        match value {
+            Value::Nil => ValueKind::Nil,
+            Value::Boolean(_) => ValueKind::Boolean,
+            Value::Integer(_) => ValueKind::Integer,
+            Value::F32(_) | Value::F64(_) => ValueKind::Float,
+            Value::String(_) => ValueKind::String,
+            Value::Binary(_) => ValueKind::Binary,
+            Value::Array(_) => ValueKind::Array,
+            Value::Map(_) => ValueKind::Map,
+            Value::Ext(ext_type, _) => ValueKind::Ext(*ext_type),
+        }
+    }
+
+    /// name like in tarantool space format
+    pub fn name(&self) -> &'static str {
+        match self {
+            ValueKind::Nil => "nil",
+            ValueKind::Boolean => "boolean",
+            ValueKind::Integer => "integer",
+            ValueKind::Float => "double",
+            ValueKind::String => "string",
+            ValueKind::Binary => "varbinary",
+            ValueKind::Array => "array",
+            ValueKind::Map => "map",
+            ValueKind::Ext(MP_DECIMAL) => "decimal",
+            ValueKind::Ext(MP_UUID) => "uuid",
+            ValueKind::Ext(MP_DATETIME) => "datetime",
+            ValueKind::Ext(MP_INTERVAL) => "interval",
+            ValueKind::Ext(_) => "ext",
+        }
+    }
+
+    /// narrowest field type which accepts the value, None for nil and unknown ext types
+    pub fn field_type(&self) -> Option<FieldType> {
+        FieldType::from_name(self.name())
+    }
+}
+
+/// Schemaless tuple, fields are rmpv values, ext values of tarantool are kept as Value::Ext
+///
+/// # Examples
+///
+/// for row in tarantool.index_iterator_all(space_name, "primary")? {
+///     let row = row?.to_value()?;
+///     let kinds: Vec<&str> = row.iter().map(|field| ValueKind::of(field).name()).collect();
+///     dump.push(row.to_json());
+/// }
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TupleValue {
+    fields: Vec<Value>,
+}
+
+impl TupleValue {
+    pub fn new(fields: Vec<Value>) -> TupleValue {
+        TupleValue { fields }
+    }
+
+    /// read msgpack array
+    pub fn from_data(data: &[u8]) -> io::Result<TupleValue> {
+        match rmpv::decode::read_value(&mut &data[..]) {
+            Ok(value) => TupleValue::from_value(value),
+            Err(e) => Err(make_error(format!("Can't read msgpack value! {}", e), false)),
+        }
+    }
+
+    pub fn from_value(value: Value) -> io::Result<TupleValue> {
+        match value {
+            Value::Array(fields) => Ok(TupleValue { fields }),
+            value => Err(make_error(format!("Tuple must be array, found {}!", ValueKind::of(&value).name()), false)),
+        }
+    }
+
+    /// json array becomes tuple, objects become maps with string keys
+    pub fn from_json(json: &JsonValue) -> io::Result<TupleValue> {
+        TupleValue::from_value(value_from_json(json))
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// field by zero based index
+    pub fn get(&self, index: usize) -> Option<&Value> {
+        self.fields.get(index)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Value> {
+        self.fields.get_mut(index)
+    }
+
+    pub fn kind(&self, index: usize) -> Option<ValueKind> {
+        self.fields.get(index).map(ValueKind::of)
+    }
+
+    pub fn push(&mut self, value: Value) {
+        self.fields.push(value);
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, Value> {
+        self.fields.iter()
+    }
+
+    pub fn fields(&self) -> &[Value] {
+        &self.fields
+    }
+
+    pub fn into_fields(self) -> Vec<Value> {
+        self.fields
+    }
+
+    pub fn to_value(&self) -> Value {
+        Value::Array(self.fields.clone())
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        JsonValue::Array(self.fields.iter().map(value_to_json).collect())
+    }
+
+    /// msgpack array, ext values are written as ext
+    pub fn encode(&self) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        serialize_to_vec(&mut buf, self)?;
+        Ok(buf)
+    }
+
+    /// decode whole tuple as T
+    pub fn decode<T>(&self) -> io::Result<T> where T: DeserializeOwned {
+        decode_serde(&self.encode()?[..])
+    }
+
+    /// decode one field as T, absent field is decoded as nil
+    pub fn decode_field<T>(&self, index: usize) -> io::Result<T> where T: DeserializeOwned {
+        let mut buf = Vec::new();
+        serialize_to_vec(&mut buf, &SerValue(self.fields.get(index).unwrap_or(&Value::Nil)))?;
+        decode_serde(&buf[..])
+    }
+
+    /// order of tuples in tarantool tree index with scalar parts, fields compared left to right, shorter tuple is less
+    pub fn compare(&self, other: &TupleValue) -> Ordering {
+        compare_keys(&self.fields, &other.fields).then(self.fields.len().cmp(&other.fields.len()))
+    }
+}
+
+impl Index<usize> for TupleValue {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Value {
+        &self.fields[index]
+    }
+}
+
+impl<'a> IntoIterator for &'a TupleValue {
+    type Item = &'a Value;
+    type IntoIter = slice::Iter<'a, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fields.iter()
+    }
+}
+
+impl From<Vec<Value>> for TupleValue {
+    fn from(fields: Vec<Value>) -> TupleValue {
+        TupleValue { fields }
+    }
+}
+
+impl From<TupleValue> for Value {
+    fn from(tuple: TupleValue) -> Value {
+        Value::Array(tuple.fields)
+    }
+}
+
+/// rmpv value written through serde with ext values kept as ext
+struct SerValue<'a>(&'a Value);
+
+impl<'a> Serialize for SerValue<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Value::Nil => serializer.serialize_unit(),
+            Value::Boolean(v) => serializer.serialize_bool(*v),
+            Value::Integer(v) => match (v.as_u64(), v.as_i64()) {
+                (Some(v), _) => serializer.serialize_u64(v),
+                (None, Some(v)) => serializer.serialize_i64(v),
+                (None, None) => serializer.serialize_unit(),
+            },
+            Value::F32(v) => serializer.serialize_f32(*v),
+            Value::F64(v) => serializer.serialize_f64(*v),
+            Value::String(v) => match v.as_str() {
+                Some(v) => serializer.serialize_str(v),
+                None => serializer.serialize_bytes(v.as_bytes()),
+            },
+            Value::Binary(v) => serializer.serialize_bytes(v),
+            Value::Array(v) => {
+                let mut seq = serializer.serialize_seq(Some(v.len()))?;
+                for value in v {
+                    seq.serialize_element(&SerValue(value))?;
+                }
+                seq.end()
+            }
+            Value::Map(v) => {
+                let mut map = serializer.serialize_map(Some(v.len()))?;
+                for (key, value) in v {
+                    map.serialize_entry(&SerValue(key), &SerValue(value))?;
+                }
+                map.end()
+            }
+            Value::Ext(ext_type, payload) => serialize_raw_ext(serializer, *ext_type, payload),
+        }
+    }
+}
+
+impl Serialize for TupleValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.fields.len()))?;
+        for value in self.fields.iter() {
+            seq.serialize_element(&SerValue(value))?;
+        }
+        seq.end()
+    }
+}
+
+/// binaries which stand for ext values inside serde are turned back to Value::Ext
+fn restore_ext(value: Value) -> Value {
+    match value {
+        Value::Binary(bytes) => match ext_from_bin(&bytes) {
+            Some((ext_type, payload)) => Value::Ext(ext_type, payload.to_vec()),
+            None => Value::Binary(bytes),
+        },
+        Value::Array(values) => Value::Array(values.into_iter().map(restore_ext).collect()),
+        Value::Map(entries) => Value::Map(entries.into_iter().map(|(k, v)| (restore_ext(k), restore_ext(v))).collect()),
+        value => value,
+    }
+}
+
+impl<'de> Deserialize<'de> for TupleValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<TupleValue, D::Error> {
+        let value = restore_ext(Value::deserialize(deserializer)?);
+        TupleValue::from_value(value).map_err(serde::de::Error::custom)
+    }
+}
+
+/// json form of msgpack value: binaries and unknown ext values are base64 strings, decimals and uuids are strings,
+/// datetimes are seconds since epoch, map keys which are not strings are written as json text
+pub fn value_to_json(value: &Value) -> JsonValue {
+    match value {
+        Value::Nil => JsonValue::Null,
+        Value::Boolean(v) => JsonValue::Bool(*v),
+        Value::Integer(v) => match (v.as_u64(), v.as_i64()) {
+            (Some(v), _) => JsonValue::from(v),
+            (None, Some(v)) => JsonValue::from(v),
+            (None, None) => JsonValue::Null,
+        },
+        Value::F32(v) => JsonValue::from(*v as f64),
+        Value::F64(v) => JsonValue::from(*v),
+        Value::String(v) => JsonValue::String(String::from_utf8_lossy(v.as_bytes()).into_owned()),
+        Value::Binary(v) => JsonValue::String(base64::encode(v)),
+        Value::Array(v) => JsonValue::Array(v.iter().map(value_to_json).collect()),
+        Value::Map(v) => JsonValue::Object(v.iter()
+            .map(|(key, value)| {
+                let key = match key {
+                    Value::String(key) => String::from_utf8_lossy(key.as_bytes()).into_owned(),
+                    key => value_to_json(key).to_string(),
+                };
+                (key, value_to_json(value))
+            })
+            .collect()),
+        Value::Ext(ext_type, payload) => {
+            let text = match *ext_type {
+                MP_DECIMAL => Decimal::from_payload(payload).map(|v| JsonValue::String(v.to_string())),
+                MP_UUID => Uuid::from_payload(payload).map(|v| JsonValue::String(v.to_string())),
+                MP_DATETIME => Datetime::from_payload(payload).map(|v| JsonValue::from(v.seconds)),
+                _ => return JsonValue::String(base64::encode(payload)),
+            };
+            text.unwrap_or_else(|_| JsonValue::String(base64::encode(payload)))
+        }
+    }
+}
+
+/// msgpack value of json, numbers are integers when they fit
+pub fn value_from_json(json: &JsonValue) -> Value {
+    match json {
+        JsonValue::Null => Value::Nil,
+        JsonValue::Bool(v) => Value::Boolean(*v),
+        JsonValue::Number(v) => match (v.as_u64(), v.as_i64(), v.as_f64()) {
+            (Some(v), _, _) => Value::from(v),
+            (None, Some(v), _) => Value::from(v),
+            (None, None, Some(v)) => Value::F64(v),
+            (None, None, None) => Value::Nil,
+        },
+        JsonValue::String(v) => Value::from(v.as_str()),
+        JsonValue::Array(v) => Value::Array(v.iter().map(value_from_json).collect()),
+        JsonValue::Object(v) => Value::Map(v.iter().map(|(key, value)| (Value::from(key.as_str()), value_from_json(value))).collect()),
+    }
+}
+
+impl<'ctx> TarantoolTuple<'ctx> {
+    /// dynamic view of the row for procs which work with any space
+    pub fn to_value(&self) -> io::Result<TupleValue> {
+        TupleValue::from_data(self.get_data())
+    }
+}
+
+enum Number {
+    Int(i128),
+    Float(f64),
+}
+
+fn as_number(value: &Value) -> Option<Number> {
+    match value {
+        Value::Integer(v) => v.as_i64().map(|v| v as i128).or_else(|| v.as_u64().map(|v| v as i128)).map(Number::Int),
+        Value::F32(v) => Some(Number::Float(*v as f64)),
+        Value::F64(v) => Some(Number::Float(*v)),
+        _ => None,
+    }
+}
+
+fn type_rank(value: &Value) -> u8 {
+    match value {
+        Value::Nil => 0,
+        Value::Boolean(_) => 1,
+        Value::Integer(_) | Value::F32(_) | Value::F64(_) => 2,
+        Value::String(_) => 3,
+        Value::Binary(_) => 4,
+        Value::Array(_) => 5,
+        Value::Map(_) => 6,
+        Value::Ext(_, _) => 7,
+    }
+}
+
+/// order of msgpack values like in tarantool scalar index: nil < bool < number < string < binary
+pub fn compare_values(a: &Value, b: &Value) -> Ordering {
+    if let (Some(a), Some(b)) = (as_number(a), as_number(b)) {
+        return match (a, b) {
+            (Number::Int(a), Number::Int(b)) => a.cmp(&b),
+            (Number::Int(a), Number::Float(b)) => (a as f64).partial_cmp(&b).unwrap_or(Ordering::Equal),
+            (Number::Float(a), Number::Int(b)) => a.partial_cmp(&(b as f64)).unwrap_or(Ordering::Equal),
+            (Number::Float(a), Number::Float(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        };
+    }
+    match (a, b) {
+        (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+        (Value::String(a), Value::String(b)) => a.as_bytes().cmp(b.as_bytes()),
+        (Value::Binary(a), Value::Binary(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => compare_keys(a, b).then(a.len().cmp(&b.len())),
+        _ => type_rank(a).cmp(&type_rank(b)),
+    }
+}
+
+/// compare keys part by part, shorter key is compared as prefix
+pub fn compare_keys(a: &[Value], b: &[Value]) -> Ordering {
+    a.iter().zip(b.iter())
+        .map(|(a, b)| compare_values(a, b))
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
@@ -0,0 +1,242 @@
+use rmp_serde::encode::StructMapWriter;
+use rmp_serde::Serializer;
+use serde::ser::{self, Serialize, SerializeMap};
+use std::cell::Cell;
+use std::io;
+use std::ptr;
+use crate::tarantool::api::*;
+use crate::tarantool::ext::ExtWriter;
+use crate::tarantool::internal::*;
+use crate::tarantool::msgpack::*;
+
+/// How structs are written by serialize_to_buf_with
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StructEncoding {
+    /// every struct is array, default of the crate
+    Array,
+    /// top level struct (row) is array, nested structs are maps with field names;
+    /// top level maps and structs of tarantool_struct_as_map! are written as maps
+    NestedMaps,
+}
+
+pub fn serialize_to_buf_with<S: Serialize>(buf: &mut Vec<u8>, v: &S, encoding: StructEncoding) -> io::Result<()> {
+    match encoding {
+        StructEncoding::Array => serialize_to_buf_mut(buf, v),
+        StructEncoding::NestedMaps => {
+            let mut named = Vec::new();
+            let is_struct = Cell::new(false);
+            v.serialize(TopLevel { serializer: &mut Serializer::with(ExtWriter::new(&mut named), StructMapWriter), is_struct: &is_struct })
+                .map_err(map_err_to_io)?;
+            //only struct written as map by StructMapWriter is converted, genuine maps are kept
+            if !is_struct.get() {
+                buf.extend_from_slice(&named);
+                return Ok(());
+            }
+            let (count, mut pos) = read_map_header(&named)?;
+            write_array_header(buf, count);
+            for _ in 0..count {
+                pos += value_len(&named[pos..])?;
+                let len = value_len(&named[pos..])?;
+                buf.extend_from_slice(&named[pos..pos + len]);
+                pos += len;
+            }
+            Ok(())
+        }
+    }
+}
+
+impl TarantoolContext {
+    /// insert with given encoding of structs
+    ///
+    /// # Examples
+    ///
+    /// tarantool.insert_with(TEST_SPACE, &row, StructEncoding::NestedMaps)?;
+    ///
+    pub fn insert_with<SER, S>(&self, space_name: S, value: &SER, encoding: StructEncoding) -> io::Result<()>
+        where SER: Serialize,
+              S: AsRef<[u8]>
+    {
+        let space_id = self.get_space_id(&space_name)?;
+        let mut buf = Vec::new();
+        serialize_to_buf_with(&mut buf, value, encoding)?;
+        let data = buf.as_ptr_range();
+        if unsafe { box_insert(space_id, data.start, data.end, ptr::null_mut()) } == -1 {
+            return make_error_result(format!("error on insert! space name={:?} ", String::from_utf8_lossy(space_name.as_ref())));
+        }
+        Ok(())
+    }
+
+    pub fn replace_with<SER, S>(&self, space_name: S, value: &SER, encoding: StructEncoding) -> io::Result<()>
+        where SER: Serialize,
+              S: AsRef<[u8]>
+    {
+        let space_id = self.get_space_id(&space_name)?;
+        let mut buf = Vec::new();
+        serialize_to_buf_with(&mut buf, value, encoding)?;
+        let data = buf.as_ptr_range();
+        if unsafe { box_replace(space_id, data.start, data.end, ptr::null_mut()) } == -1 {
+            return make_error_result(format!("error on replace! space name={:?} ", String::from_utf8_lossy(space_name.as_ref())));
+        }
+        Ok(())
+    }
+}
+
+/// Serializer adapter which tells if value given to it is written as struct
+struct TopLevel<'a, S> {
+    serializer: S,
+    is_struct: &'a Cell<bool>,
+}
+
+impl<'a, S> ser::Serializer for TopLevel<'a, S> where S: ser::Serializer {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = S::SerializeSeq;
+    type SerializeTuple = S::SerializeTuple;
+    type SerializeTupleStruct = S::SerializeTupleStruct;
+    type SerializeTupleVariant = S::SerializeTupleVariant;
+    type SerializeMap = S::SerializeMap;
+    type SerializeStruct = S::SerializeStruct;
+    type SerializeStructVariant = S::SerializeStructVariant;
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct, Self::Error> {
+        self.is_struct.set(true);
+        self.serializer.serialize_struct(name, len)
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_bool(v) }
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_i8(v) }
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_i16(v) }
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_i32(v) }
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_i64(v) }
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_u8(v) }
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_u16(v) }
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_u32(v) }
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_u64(v) }
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_f32(v) }
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_f64(v) }
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_char(v) }
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_str(v) }
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_bytes(v) }
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_none() }
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_unit() }
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> { self.serializer.serialize_unit_struct(name) }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        self.serializer.serialize_some(value)
+    }
+
+    fn serialize_unit_variant(self, name: &'static str, variant_index: u32, variant: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.serializer.serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, name: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
+        self.serializer.serialize_newtype_struct(name, value)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, name: &'static str, variant_index: u32, variant: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
+        self.serializer.serialize_newtype_variant(name, variant_index, variant, value)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> { self.serializer.serialize_seq(len) }
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> { self.serializer.serialize_tuple(len) }
+
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serializer.serialize_tuple_struct(name, len)
+    }
+
+    fn serialize_tuple_variant(self, name: &'static str, variant_index: u32, variant: &'static str, len: usize) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        self.serializer.serialize_tuple_variant(name, variant_index, variant, len)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> { self.serializer.serialize_map(len) }
+
+    fn serialize_struct_variant(self, name: &'static str, variant_index: u32, variant: &'static str, len: usize) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.serializer.serialize_struct_variant(name, variant_index, variant, len)
+    }
+}
+
+/// Serializer adapter which writes struct given to it as map, see tarantool_struct_as_map!
+pub struct StructAsMap<S>(pub S);
+
+/// Fields of struct written as map entries
+pub struct MapStruct<M>(M);
+
+impl<M> ser::SerializeStruct for MapStruct<M> where M: SerializeMap {
+    type Ok = M::Ok;
+    type Error = M::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error> {
+        self.0.serialize_entry(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+
+impl<S> ser::Serializer for StructAsMap<S> where S: ser::Serializer {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = S::SerializeSeq;
+    type SerializeTuple = S::SerializeTuple;
+    type SerializeTupleStruct = S::SerializeTupleStruct;
+    type SerializeTupleVariant = S::SerializeTupleVariant;
+    type SerializeMap = S::SerializeMap;
+    type SerializeStruct = MapStruct<S::SerializeMap>;
+    type SerializeStructVariant = S::SerializeStructVariant;
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct, Self::Error> {
+        self.0.serialize_map(Some(len)).map(MapStruct)
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> { self.0.serialize_bool(v) }
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> { self.0.serialize_i8(v) }
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> { self.0.serialize_i16(v) }
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> { self.0.serialize_i32(v) }
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> { self.0.serialize_i64(v) }
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> { self.0.serialize_u8(v) }
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> { self.0.serialize_u16(v) }
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> { self.0.serialize_u32(v) }
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> { self.0.serialize_u64(v) }
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> { self.0.serialize_f32(v) }
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> { self.0.serialize_f64(v) }
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> { self.0.serialize_char(v) }
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> { self.0.serialize_str(v) }
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> { self.0.serialize_bytes(v) }
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> { self.0.serialize_none() }
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> { self.0.serialize_unit() }
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> { self.0.serialize_unit_struct(name) }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_some(value)
+    }
+
+    fn serialize_unit_variant(self, name: &'static str, variant_index: u32, variant: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, name: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_newtype_struct(name, value)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, name: &'static str, variant_index: u32, variant: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_newtype_variant(name, variant_index, variant, value)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> { self.0.serialize_seq(len) }
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> { self.0.serialize_tuple(len) }
+
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.0.serialize_tuple_struct(name, len)
+    }
+
+    fn serialize_tuple_variant(self, name: &'static str, variant_index: u32, variant: &'static str, len: usize) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        self.0.serialize_tuple_variant(name, variant_index, variant, len)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> { self.0.serialize_map(len) }
+
+    fn serialize_struct_variant(self, name: &'static str, variant_index: u32, variant: &'static str, len: usize) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.0.serialize_struct_variant(name, variant_index, variant, len)
+    }
+}
@@ -0,0 +1,706 @@
+use rmp::Marker;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use crate::tarantool::internal::make_error;
+use crate::tarantool::msgpack::*;
+
+/// tarantool msgpack ext types, see mp_extension_type in tarantool sources
+pub const MP_DECIMAL: i8 = 1;
+pub const MP_UUID: i8 = 2;
+pub const MP_DATETIME: i8 = 4;
+pub const MP_INTERVAL: i8 = 6;
+
+/// max digits of tarantool decimal
+const DECIMAL_MAX_DIGITS: usize = 38;
+
+lazy_static! {
+    /// rmp-serde can't read or write ext values, inside serde they are binaries with this prefix followed by ext type;
+    /// random tail of prefix keeps binaries of users from being taken for ext values
+    static ref EXT_BIN_PREFIX: Vec<u8> = {
+        let mut prefix = b"\xc1tnt.ext".to_vec();
+        prefix.extend_from_slice(&RandomState::new().build_hasher().finish().to_le_bytes());
+        prefix
+    };
+}
+
+/// Binary written through serde which ExtWriter replaces
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum PendingBin {
+    None,
+    /// binary of ext type, encoded size given
+    Ext(usize),
+    /// msgpack value written as is, encoded size of binary given
+    Raw(usize),
+}
+
+thread_local! {
+    /// number of live ExtWriters, ext values are written as plain binaries outside of them
+    static EXT_WRITERS: Cell<u32> = const { Cell::new(0) };
+    /// set right before binary is written, taken by ExtWriter on next write;
+    /// cleared once the binary is written, other serializers don't take it
+    static PENDING_BIN: Cell<PendingBin> = const { Cell::new(PendingBin::None) };
+}
+
+fn ext_error<T>(message: String) -> io::Result<T> {
+    Err(make_error(message, false))
+}
+
+/// Writer passing msgpack to inner writer as it is written,
+/// only binaries written by ext types and raw values are collected and replaced
+pub(crate) struct ExtWriter<W: io::Write> {
+    inner: W,
+    pending: PendingBin,
+    /// bytes of pending binary still to come
+    left: usize,
+    bin: Vec<u8>,
+}
+
+impl<W: io::Write> ExtWriter<W> {
+    pub(crate) fn new(inner: W) -> ExtWriter<W> {
+        EXT_WRITERS.with(|v| v.set(v.get() + 1));
+        clear_pending_bin();
+        ExtWriter { inner, pending: PendingBin::None, left: 0, bin: Vec::new() }
+    }
+
+    fn write_pending(&mut self) -> io::Result<()> {
+        let bytes = match read_bin(&self.bin)? {
+            Some((bytes, _)) => bytes,
+            None => return ext_error("Binary of ext value is broken!".to_string()),
+        };
+        match self.pending {
+            PendingBin::Ext(_) => match ext_from_bin(bytes) {
+                Some((ext_type, payload)) => {
+                    let mut ext = Vec::with_capacity(bytes.len() + 6);
+                    write_ext(&mut ext, ext_type, payload);
+                    self.inner.write_all(&ext)?;
+                }
+                None => return ext_error("Binary of ext value is broken!".to_string()),
+            },
+            PendingBin::Raw(_) => self.inner.write_all(bytes)?,
+            PendingBin::None => {}
+        }
+        self.pending = PendingBin::None;
+        self.bin.clear();
+        Ok(())
+    }
+}
+
+impl<W: io::Write> io::Write for ExtWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if self.pending == PendingBin::None {
+            self.pending = PENDING_BIN.with(|v| v.replace(PendingBin::None));
+            self.left = match self.pending {
+                PendingBin::None => return self.inner.write(data),
+                PendingBin::Ext(len) | PendingBin::Raw(len) => len,
+            };
+        }
+        let len = data.len().min(self.left);
+        self.bin.extend_from_slice(&data[..len]);
+        self.left -= len;
+        if self.left == 0 {
+            if let Err(e) = self.write_pending() {
+                self.pending = PendingBin::None;
+                self.bin.clear();
+                return Err(e);
+            }
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: io::Write> Drop for ExtWriter<W> {
+    fn drop(&mut self) {
+        EXT_WRITERS.with(|v| v.set(v.get() - 1));
+        clear_pending_bin();
+    }
+}
+
+fn clear_pending_bin() {
+    PENDING_BIN.with(|v| v.set(PendingBin::None));
+}
+
+/// serialize binary which ExtWriter replaces, slot is cleared after it even if binary went to other serializer or failed
+fn serialize_pending_bin<S: Serializer>(serializer: S, pending: PendingBin, bytes: &[u8]) -> Result<S::Ok, S::Error> {
+    if ext_writer_active() {
+        PENDING_BIN.with(|v| v.set(pending));
+    }
+    let res = serializer.serialize_bytes(bytes);
+    clear_pending_bin();
+    res
+}
+
+/// true when value is serialized by ExtWriter, raw msgpack can be passed through then
+pub(crate) fn ext_writer_active() -> bool {
+    EXT_WRITERS.with(|v| v.get()) > 0
+}
+
+/// write encoded msgpack value as it is, must be called only when ext_writer_active
+pub(crate) fn serialize_raw_msgpack<S: Serializer>(serializer: S, data: &[u8]) -> Result<S::Ok, S::Error> {
+    serialize_pending_bin(serializer, PendingBin::Raw(bin_len(data.len())), data)
+}
+
+/// Reader of one msgpack value, ext values in it are read as binaries which serde can pass to ext types
+pub(crate) struct ExtReader<R: io::Read> {
+    inner: R,
+    /// values still to read, reader ends after the first value
+    values: usize,
+    /// bytes of current value which are passed as they are
+    skip: usize,
+    /// marker and size field of current value
+    head: [u8; 5],
+    head_len: usize,
+    head_pos: usize,
+    /// ext value converted to binary
+    ext: Vec<u8>,
+    ext_pos: usize,
+}
+
+impl<R: io::Read> ExtReader<R> {
+    pub(crate) fn new(inner: R) -> ExtReader<R> {
+        clear_pending_bin();
+        ExtReader { inner, values: 1, skip: 0, head: [0; 5], head_len: 0, head_pos: 0, ext: Vec::new(), ext_pos: 0 }
+    }
+
+    fn next_value(&mut self) -> io::Result<()> {
+        self.values -= 1;
+        self.inner.read_exact(&mut self.head[..1])?;
+        let marker = Marker::from_u8(self.head[0]);
+        let (size_len, body) = marker_layout(marker)?;
+        self.inner.read_exact(&mut self.head[1..1 + size_len])?;
+        let size = self.head[1..1 + size_len].iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        self.head_len = 1 + size_len;
+        self.head_pos = 0;
+        match body {
+            Body::Bytes(n) => self.skip = n,
+            Body::SizedBytes(extra) => self.skip = size + extra,
+            Body::Values(n) => self.values += n,
+            Body::SizedValues(multiplier) => self.values += size * multiplier,
+        }
+        if is_ext_marker(marker) {
+            let mut ext = self.head[..self.head_len].to_vec();
+            ext.resize(self.head_len + self.skip, 0);
+            self.inner.read_exact(&mut ext[self.head_len..])?;
+            let (ext_type, payload, _) = read_ext(&ext)?.ok_or_else(|| make_error("msgpack ext expected!".to_string(), false))?;
+            self.ext.clear();
+            write_bin(&mut self.ext, &ext_bin(ext_type, payload));
+            self.ext_pos = 0;
+            self.skip = 0;
+            self.head_len = 0;
+        }
+        Ok(())
+    }
+}
+
+impl<R: io::Read> Drop for ExtReader<R> {
+    fn drop(&mut self) {
+        clear_pending_bin();
+    }
+}
+
+impl<R: io::Read> io::Read for ExtReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.head_pos < self.head_len {
+                let len = buf.len().min(self.head_len - self.head_pos);
+                buf[..len].copy_from_slice(&self.head[self.head_pos..self.head_pos + len]);
+                self.head_pos += len;
+                return Ok(len);
+            }
+            if self.ext_pos < self.ext.len() {
+                let len = buf.len().min(self.ext.len() - self.ext_pos);
+                buf[..len].copy_from_slice(&self.ext[self.ext_pos..self.ext_pos + len]);
+                self.ext_pos += len;
+                return Ok(len);
+            }
+            if self.skip > 0 {
+                let len = buf.len().min(self.skip);
+                let len = self.inner.read(&mut buf[..len])?;
+                self.skip -= len;
+                return Ok(len);
+            }
+            if self.values == 0 || buf.is_empty() {
+                return Ok(0);
+            }
+            self.next_value()?;
+        }
+    }
+}
+
+/// copy of msgpack value with ext values converted to binaries readable by serde, None if data has no ext values
+pub(crate) fn ext_to_bins(data: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    if !scan_value(data)?.1 {
+        return Ok(None);
+    }
+    let mut out = Vec::with_capacity(data.len() + 16);
+    rewrite_value(data, &mut out, &mut |data: &[u8], out: &mut Vec<u8>| match read_ext(data)? {
+        Some((ext_type, payload, len)) => {
+            write_bin(out, &ext_bin(ext_type, payload));
+            Ok(Some(len))
+        }
+        None => Ok(None),
+    })?;
+    Ok(Some(out))
+}
+
+/// binary which stands for ext value inside serde
+fn ext_bin(ext_type: i8, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(EXT_BIN_PREFIX.len() + 1 + payload.len());
+    bytes.extend_from_slice(&EXT_BIN_PREFIX);
+    bytes.push(ext_type as u8);
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+/// Rust type of tarantool ext value
+pub(crate) trait ExtValue: Sized {
+    const EXT_TYPE: i8;
+    const EXPECTING: &'static str;
+
+    fn write_payload(&self, buf: &mut Vec<u8>);
+
+    fn from_payload(payload: &[u8]) -> io::Result<Self>;
+
+    /// values given as string, like uuid or decimal from lua
+    fn from_text(_text: &str) -> Option<io::Result<Self>> {
+        None
+    }
+
+    fn from_i64(_value: i64) -> Option<io::Result<Self>> {
+        None
+    }
+}
+
+fn serialize_ext<T: ExtValue, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut payload = Vec::with_capacity(16);
+    value.write_payload(&mut payload);
+    serialize_raw_ext(serializer, T::EXT_TYPE, &payload)
+}
+
+/// write ext value of any type through serde
+pub(crate) fn serialize_raw_ext<S: Serializer>(serializer: S, ext_type: i8, payload: &[u8]) -> Result<S::Ok, S::Error> {
+    let bytes = ext_bin(ext_type, payload);
+    serialize_pending_bin(serializer, PendingBin::Ext(bin_len(bytes.len())), &bytes)
+}
+
+/// ext type and payload of binary which stands for ext value inside serde
+pub(crate) fn ext_from_bin(bytes: &[u8]) -> Option<(i8, &[u8])> {
+    if bytes.len() > EXT_BIN_PREFIX.len() && bytes.starts_with(&EXT_BIN_PREFIX) {
+        Some((bytes[EXT_BIN_PREFIX.len()] as i8, &bytes[EXT_BIN_PREFIX.len() + 1..]))
+    } else {
+        None
+    }
+}
+
+struct ExtVisitor<T>(PhantomData<T>);
+
+impl<'de, T: ExtValue> Visitor<'de> for ExtVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(T::EXPECTING)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<T, E> {
+        match ext_from_bin(v) {
+            Some((ext_type, payload)) if ext_type == T::EXT_TYPE => T::from_payload(payload).map_err(E::custom),
+            Some((ext_type, _)) => Err(E::custom(format!("invalid type: msgpack ext {}, expected {}", ext_type, T::EXPECTING))),
+            None => Err(E::invalid_type(de::Unexpected::Bytes(v), &self)),
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        match T::from_text(v) {
+            Some(res) => res.map_err(E::custom),
+            None => Err(E::invalid_type(de::Unexpected::Str(v), &self)),
+        }
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<T, E> {
+        match T::from_i64(v) {
+            Some(res) => res.map_err(E::custom),
+            None => Err(E::invalid_type(de::Unexpected::Signed(v), &self)),
+        }
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<T, E> {
+        if v > i64::MAX as u64 {
+            return Err(E::invalid_type(de::Unexpected::Unsigned(v), &self));
+        }
+        self.visit_i64(v as i64)
+    }
+}
+
+macro_rules! ext_serde {
+    ($name:ident) => {
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serialize_ext(self, serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$name, D::Error> {
+                deserializer.deserialize_any(ExtVisitor(PhantomData))
+            }
+        }
+    };
+}
+
+/// Tarantool uuid, bytes are in network order as in text form
+///
+/// # Examples
+///
+/// let id: Uuid = "64d22e4d-ac92-4a23-899a-e59f34af5479".parse()?;
+///
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Uuid([u8; 16]);
+
+impl Uuid {
+    pub fn from_bytes(bytes: [u8; 16]) -> Uuid {
+        Uuid(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    pub fn is_nil(&self) -> bool {
+        self.0 == [0; 16]
+    }
+}
+
+impl fmt::Display for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, b) in self.0.iter().enumerate() {
+            if i == 4 || i == 6 || i == 8 || i == 10 {
+                f.write_str("-")?;
+            }
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Uuid {
+    type Err = io::Error;
+
+    /// hyphenated or 32 hex digits
+    fn from_str(s: &str) -> io::Result<Uuid> {
+        let hex: Vec<u8> = match s.len() {
+            32 => s.bytes().collect(),
+            36 if [8, 13, 18, 23].iter().all(|i| s.as_bytes()[*i] == b'-') => s.bytes().filter(|b| *b != b'-').collect(),
+            _ => return ext_error(format!("Invalid uuid {:?}!", s)),
+        };
+        let mut bytes = [0u8; 16];
+        for (i, pair) in hex.chunks(2).enumerate() {
+            bytes[i] = std::str::from_utf8(pair).ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| make_error(format!("Invalid uuid {:?}!", s), false))?;
+        }
+        Ok(Uuid(bytes))
+    }
+}
+
+impl ExtValue for Uuid {
+    const EXT_TYPE: i8 = MP_UUID;
+    const EXPECTING: &'static str = "uuid";
+
+    fn write_payload(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.0);
+    }
+
+    fn from_payload(payload: &[u8]) -> io::Result<Uuid> {
+        if payload.len() != 16 {
+            return ext_error(format!("Invalid uuid size {}!", payload.len()));
+        }
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(payload);
+        Ok(Uuid(bytes))
+    }
+
+    fn from_text(text: &str) -> Option<io::Result<Uuid>> {
+        Some(text.parse())
+    }
+}
+
+ext_serde!(Uuid);
+
+/// Tarantool decimal, value is mantissa * 10^-scale, up to 38 digits
+///
+/// equality compares representation, 1.0 and 1.00 differ
+///
+/// # Examples
+///
+/// let price: Decimal = "12.30".parse()?;
+/// assert_eq!((price.mantissa(), price.scale()), (1230, 2));
+///
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    pub fn new(mantissa: i128, scale: u32) -> io::Result<Decimal> {
+        if mantissa.unsigned_abs().to_string().len() > DECIMAL_MAX_DIGITS || scale as usize > DECIMAL_MAX_DIGITS {
+            return ext_error(format!("Decimal {}e-{} is out of range!", mantissa, scale));
+        }
+        Ok(Decimal { mantissa, scale })
+    }
+
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Decimal {
+        Decimal { mantissa: value as i128, scale: 0 }
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (int_part, frac_part) = digits.split_at(digits.len() - scale);
+        write!(f, "{}{}.{}", sign, int_part, frac_part)
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Decimal> {
+        let (negative, unsigned) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (int_part, frac_part) = match unsigned.find('.') {
+            Some(pos) => (&unsigned[..pos], &unsigned[pos + 1..]),
+            None => (unsigned, ""),
+        };
+        if int_part.is_empty() && frac_part.is_empty() || !int_part.bytes().chain(frac_part.bytes()).all(|b| b.is_ascii_digit()) {
+            return ext_error(format!("Invalid decimal {:?}!", s));
+        }
+        let digits = format!("{}{}", int_part, frac_part);
+        let digits = digits.trim_start_matches('0');
+        if digits.len() > DECIMAL_MAX_DIGITS {
+            return ext_error(format!("Decimal {:?} has more than {} digits!", s, DECIMAL_MAX_DIGITS));
+        }
+        let mantissa = if digits.is_empty() { 0 } else { digits.parse::<i128>().map_err(|e| make_error(e.to_string(), false))? };
+        Decimal::new(if negative { -mantissa } else { mantissa }, frac_part.len() as u32)
+    }
+}
+
+impl ExtValue for Decimal {
+    const EXT_TYPE: i8 = MP_DECIMAL;
+    const EXPECTING: &'static str = "decimal";
+
+    /// scale as msgpack int followed by packed bcd digits with sign in the last nibble
+    fn write_payload(&self, buf: &mut Vec<u8>) {
+        let _ = rmp::encode::write_sint(buf, self.scale as i64);
+        let mut nibbles: Vec<u8> = self.mantissa.unsigned_abs().to_string().bytes().map(|b| b - b'0').collect();
+        nibbles.push(if self.mantissa < 0 { 0x0d } else { 0x0c });
+        if nibbles.len() % 2 == 1 {
+            nibbles.insert(0, 0);
+        }
+        buf.extend(nibbles.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    }
+
+    fn from_payload(payload: &[u8]) -> io::Result<Decimal> {
+        let (scale, pos) = read_int(payload)?;
+        let bcd = &payload[pos..];
+        let (sign, digits) = match bcd.split_last() {
+            Some((last, digits)) => (last & 0x0f, digits.iter().flat_map(|b| vec![b >> 4, b & 0x0f]).chain(Some(last >> 4))),
+            None => return ext_error("Empty decimal!".to_string()),
+        };
+        let mut mantissa: i128 = 0;
+        for digit in digits {
+            if digit > 9 {
+                return ext_error(format!("Invalid decimal digit {}!", digit));
+            }
+            mantissa = mantissa.checked_mul(10).and_then(|v| v.checked_add(digit as i128))
+                .ok_or_else(|| make_error("Decimal is out of range!".to_string(), false))?;
+        }
+        if sign == 0x0b || sign == 0x0d {
+            mantissa = -mantissa;
+        }
+        if scale < 0 {
+            //exponent form like 1e5
+            let exp = 10i128.checked_pow((-scale) as u32)
+                .ok_or_else(|| make_error(format!("Decimal scale {} is out of range!", scale), false))?;
+            let mantissa = mantissa.checked_mul(exp).ok_or_else(|| make_error("Decimal is out of range!".to_string(), false))?;
+            return Decimal::new(mantissa, 0);
+        }
+        Decimal::new(mantissa, scale as u32)
+    }
+
+    fn from_text(text: &str) -> Option<io::Result<Decimal>> {
+        Some(text.parse())
+    }
+
+    fn from_i64(value: i64) -> Option<io::Result<Decimal>> {
+        Some(Ok(Decimal::from(value)))
+    }
+}
+
+ext_serde!(Decimal);
+
+/// Tarantool datetime, seconds since unix epoch in UTC, tzoffset in minutes
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Datetime {
+    pub seconds: i64,
+    pub nsec: i32,
+    pub tzoffset: i16,
+    /// index of timezone name in tarantool timezone table, 0 if not set
+    pub tzindex: i16,
+}
+
+impl Datetime {
+    pub fn from_timestamp(seconds: i64, nsec: i32) -> Datetime {
+        Datetime { seconds, nsec, ..Default::default() }
+    }
+}
+
+impl ExtValue for Datetime {
+    const EXT_TYPE: i8 = MP_DATETIME;
+    const EXPECTING: &'static str = "datetime";
+
+    /// little endian seconds, other fields are written only if any of them is set
+    fn write_payload(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.seconds.to_le_bytes());
+        if self.nsec != 0 || self.tzoffset != 0 || self.tzindex != 0 {
+            buf.extend_from_slice(&self.nsec.to_le_bytes());
+            buf.extend_from_slice(&self.tzoffset.to_le_bytes());
+            buf.extend_from_slice(&self.tzindex.to_le_bytes());
+        }
+    }
+
+    fn from_payload(payload: &[u8]) -> io::Result<Datetime> {
+        let mut datetime = Datetime::default();
+        match payload.len() {
+            8 | 16 => {
+                let mut seconds = [0u8; 8];
+                seconds.copy_from_slice(&payload[..8]);
+                datetime.seconds = i64::from_le_bytes(seconds);
+            }
+            len => return ext_error(format!("Invalid datetime size {}!", len)),
+        }
+        if payload.len() == 16 {
+            datetime.nsec = i32::from_le_bytes([payload[8], payload[9], payload[10], payload[11]]);
+            datetime.tzoffset = i16::from_le_bytes([payload[12], payload[13]]);
+            datetime.tzindex = i16::from_le_bytes([payload[14], payload[15]]);
+        }
+        Ok(datetime)
+    }
+}
+
+ext_serde!(Datetime);
+
+/// How interval arithmetic treats month ends, tarantool default is None
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum IntervalAdjust {
+    Excess = 0,
+    #[default]
+    None = 1,
+    Last = 2,
+}
+
+/// Tarantool datetime interval
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Interval {
+    pub year: i64,
+    pub month: i64,
+    pub week: i64,
+    pub day: i64,
+    pub hour: i64,
+    pub min: i64,
+    pub sec: i64,
+    pub nsec: i64,
+    pub adjust: IntervalAdjust,
+}
+
+/// field ids of interval payload
+const INTERVAL_ADJUST: u64 = 8;
+
+impl Interval {
+    fn fields(&self) -> [i64; 8] {
+        [self.year, self.month, self.week, self.day, self.hour, self.min, self.sec, self.nsec]
+    }
+}
+
+impl ExtValue for Interval {
+    const EXT_TYPE: i8 = MP_INTERVAL;
+    const EXPECTING: &'static str = "interval";
+
+    /// count of fields as one byte, then pairs of field id and value, zero fields are omitted
+    fn write_payload(&self, buf: &mut Vec<u8>) {
+        let fields = self.fields();
+        let adjust = self.adjust != IntervalAdjust::None;
+        buf.push(fields.iter().filter(|v| **v != 0).count() as u8 + adjust as u8);
+        for (id, value) in fields.iter().enumerate().filter(|(_, v)| **v != 0) {
+            let _ = rmp::encode::write_uint(buf, id as u64);
+            let _ = rmp::encode::write_sint(buf, *value);
+        }
+        if adjust {
+            let _ = rmp::encode::write_uint(buf, INTERVAL_ADJUST);
+            let _ = rmp::encode::write_sint(buf, self.adjust as i64);
+        }
+    }
+
+    fn from_payload(payload: &[u8]) -> io::Result<Interval> {
+        let count = match payload.first() {
+            Some(count) => *count,
+            None => return ext_error("Empty interval!".to_string()),
+        };
+        let mut interval = Interval::default();
+        let mut pos = 1;
+        for _ in 0..count {
+            let (id, len) = read_int(&payload[pos..])?;
+            pos += len;
+            let (value, len) = read_int(&payload[pos..])?;
+            pos += len;
+            match id {
+                0 => interval.year = value,
+                1 => interval.month = value,
+                2 => interval.week = value,
+                3 => interval.day = value,
+                4 => interval.hour = value,
+                5 => interval.min = value,
+                6 => interval.sec = value,
+                7 => interval.nsec = value,
+                8 => interval.adjust = match value {
+                    0 => IntervalAdjust::Excess,
+                    1 => IntervalAdjust::None,
+                    2 => IntervalAdjust::Last,
+                    _ => return ext_error(format!("Invalid interval adjust {}!", value)),
+                },
+                _ => return ext_error(format!("Invalid interval field {}!", id)),
+            }
+        }
+        Ok(interval)
+    }
+}
+
+ext_serde!(Interval);
@@ -0,0 +1,122 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io;
+use std::ptr;
+use crate::tarantool::api::*;
+use crate::tarantool::dict::*;
+use crate::tarantool::internal::*;
+use crate::tarantool::msgpack::*;
+
+impl TarantoolContext {
+    /// zero based number of field by its name in space format
+    pub fn field_no(&self, space_id: u32, field_name: &str) -> io::Result<u32> {
+        if let Some(field_no) = search_field_no(space_id, field_name) {
+            return Ok(field_no);
+        }
+        self.space_info_by_id(space_id)?
+            .field_no(field_name)
+            .ok_or_else(|| make_error(format!("Unknown field name {}! space id={}", field_name, space_id), false))
+    }
+
+    /// numbers of space fields for fields of struct T
+    fn struct_field_nos<T>(&self, space_id: u32) -> io::Result<Vec<u32>> where T: DeserializeOwned {
+        let names = struct_field_names::<T>()
+            .ok_or_else(|| make_error(format!("Type is not a struct, fields can't be mapped by name! space id={}", space_id), false))?;
+        names.iter().map(|name| self.field_no(space_id, name)).collect()
+    }
+
+    /// serialize struct as tuple placing every struct field to the space field with the same name,
+    /// space fields absent in struct are nil, trailing ones are omitted
+    pub fn encode_by_names<T>(&self, space_id: u32, value: &T) -> io::Result<Vec<u8>>
+        where T: Serialize + DeserializeOwned
+    {
+        let field_nos = self.struct_field_nos::<T>(space_id)?;
+        let mut buf = Vec::new();
+        serialize_to_buf_mut(&mut buf, value)?;
+
+        let (count, mut pos) = read_array_header(&buf)?;
+        if count as usize != field_nos.len() {
+            return make_error_result(format!("Struct is serialized with {} fields instead of {}! space id={}", count, field_nos.len(), space_id));
+        }
+        let mut fields: Vec<Option<&[u8]>> = vec![None; field_nos.iter().max().map(|v| *v as usize + 1).unwrap_or(0)];
+        for field_no in field_nos.iter() {
+            let len = value_len(&buf[pos..])?;
+            fields[*field_no as usize] = Some(&buf[pos..pos + len]);
+            pos += len;
+        }
+
+        let mut tuple = Vec::with_capacity(buf.len() + fields.len());
+        write_array_header(&mut tuple, fields.len() as u32);
+        for field in fields {
+            match field {
+                Some(field) => tuple.extend_from_slice(field),
+                None => tuple.push(MSGPACK_NIL),
+            }
+        }
+        Ok(tuple)
+    }
+
+    pub fn insert_by_names<T, S>(&self, space_name: S, value: &T) -> io::Result<()>
+        where T: Serialize + DeserializeOwned,
+              S: AsRef<[u8]>
+    {
+        let space_id = self.get_space_id(&space_name)?;
+        let tuple = self.encode_by_names(space_id, value)?;
+        unsafe {
+            let ptr_start = tuple.as_ptr();
+            if box_insert(space_id, ptr_start, ptr_start.add(tuple.len()), ptr::null_mut()) == -1 {
+                return make_error_result(format!("error on insert! space name={:?} ", String::from_utf8_lossy(space_name.as_ref())));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn replace_by_names<T, S>(&self, space_name: S, value: &T) -> io::Result<()>
+        where T: Serialize + DeserializeOwned,
+              S: AsRef<[u8]>
+    {
+        let space_id = self.get_space_id(&space_name)?;
+        let tuple = self.encode_by_names(space_id, value)?;
+        unsafe {
+            let ptr_start = tuple.as_ptr();
+            if box_replace(space_id, ptr_start, ptr_start.add(tuple.len()), ptr::null_mut()) == -1 {
+                return make_error_result(format!("error on replace! space name={:?} ", String::from_utf8_lossy(space_name.as_ref())));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'ctx> TarantoolTuple<'ctx> {
+    fn space_id_or_error(&self) -> io::Result<u32> {
+        self.space_id()
+            .ok_or_else(|| make_error("Tuple is not bound to space, fields can't be accessed by name!".to_string(), false))
+    }
+
+    /// zero based number of field by its name in format of tuple space
+    pub fn field_no(&self, name: &str) -> io::Result<u32> {
+        TarantoolContext::new_ffi().field_no(self.space_id_or_error()?, name)
+    }
+
+    /// decode field by its name in space format, absent trailing field is decoded as nil
+    ///
+    /// # Examples
+    ///
+    /// let name: String = row.get("name")?;
+    ///
+    pub fn get<V>(&self, name: &str) -> io::Result<V> where V: DeserializeOwned {
+        match self.get_field_data(self.field_no(name)?)? {
+            Some(field) => decode_serde(field),
+            None => decode_serde(&[MSGPACK_NIL][..]),
+        }
+    }
+
+    /// decode tuple as struct T taking every struct field from the space field with the same name,
+    /// declaration order of struct fields doesn't matter
+    pub fn decode_by_names<T>(&self) -> io::Result<T> where T: DeserializeOwned {
+        let field_nos = TarantoolContext::new_ffi().struct_field_nos::<T>(self.space_id_or_error()?)?;
+        let mut buf = Vec::new();
+        self.copy_fields_to_buf(&field_nos, &mut buf)?;
+        decode_serde(&buf[..])
+    }
+}
@@ -74,24 +74,42 @@ extern "C" {
     pub fn box_index_min(space_id: u32, index_id: u32, key: *const c_uchar, key_end: *const c_uchar, box_tuple_t: *mut *mut c_uchar) -> c_int;
     pub fn box_index_max(space_id: u32, index_id: u32, key: *const c_uchar, key_end: *const c_uchar, box_tuple_t: *mut *mut c_uchar) -> c_int;
     pub fn box_index_count(space_id: u32, index_id: u32, p_type: c_uchar, key: *const c_uchar, key_end: *const c_uchar) -> isize;
+    pub fn box_index_len(space_id: u32, index_id: u32) -> isize;
+    pub fn box_index_bsize(space_id: u32, index_id: u32) -> isize;
+    pub fn box_index_random(space_id: u32, index_id: u32, rnd: u32, box_tuple_t: *mut *mut c_uchar) -> c_int;
 
 
     pub fn box_key_def_new(fields: *const u32, types: *const u32, part_count: u32) -> *const c_uchar;
+    pub fn box_key_def_delete(key_def: *const c_uchar);
+    pub fn box_tuple_compare(tuple_a: *const c_uchar, tuple_b: *const c_uchar, key_def: *const c_uchar) -> c_int;
+    pub fn box_tuple_compare_with_key(tuple_a: *const c_uchar, key_b: *const c_uchar, key_def: *const c_uchar) -> c_int;
+    pub fn box_tuple_extract_key(box_tuple_t: *const c_uchar, space_id: u32, index_id: u32, key_size: *mut u32) -> *const c_uchar;
     pub fn box_tuple_format_new(keys: *const  *const c_uchar, key_count: u16) -> *const c_uchar;
     pub fn box_tuple_format_default() -> *const c_uchar;
     pub fn box_tuple_new(format: *const c_uchar, data: *const c_uchar, end: *const c_uchar) -> *const c_uchar;
     pub fn box_tuple_field(box_tuple_t: *const c_uchar, fieldno: c_int) -> *const c_uchar;
+    pub fn box_tuple_ref(box_tuple_t: *const c_uchar) -> c_int;
+    pub fn box_tuple_unref(box_tuple_t: *const c_uchar);
+    pub fn box_tuple_field_count(box_tuple_t: *const c_uchar) -> u32;
     pub fn box_tuple_bsize(box_tuple_t: *const c_uchar) -> usize;
     pub fn box_tuple_to_buf(box_tuple_t: *const c_uchar, buf: *const c_uchar, size: usize) -> usize;
+    pub fn box_tuple_update(box_tuple_t: *const c_uchar, expr: *const c_uchar, expr_end: *const c_uchar) -> *const c_uchar;
+    pub fn box_tuple_upsert(box_tuple_t: *const c_uchar, expr: *const c_uchar, expr_end: *const c_uchar) -> *const c_uchar;
 
     pub fn box_space_id_by_name(name: *const c_uchar, len: u32) -> u32;
     pub fn box_index_id_by_name(space_id: u32, name: *const c_uchar, len: u32) -> u32;
 
+    pub fn box_schema_version() -> u64;
+
     pub fn box_txn_begin() -> c_int;
     pub fn box_txn_commit() -> c_int;
     pub fn box_txn_rollback() -> c_int;
     pub fn box_txn_id() -> i64;
 
+    pub fn box_latch_new() -> *mut c_uchar;
+    pub fn box_latch_lock(latch: *mut c_uchar);
+    pub fn box_latch_unlock(latch: *mut c_uchar);
+
     //    pub fn box_error_code(box_error_t: *const c_uchar) -> u32;
     pub fn box_error_message(box_error_t: *const c_uchar) -> *const c_char;
     pub fn box_error_last() -> *const c_uchar;
@@ -108,6 +126,8 @@ extern "C" {
 
     //set function name
     pub fn lua_getfield(lua_state: *const c_int, idx: c_int, k: *const c_uchar);
+    //load lua chunk as function
+    pub fn luaL_loadbuffer(lua_state: *const c_int, buff: *const c_uchar, sz: usize, name: *const c_uchar) -> c_int;
 
     //call function
     pub fn luaT_call(lua_state: *const c_int, nargs: c_int, nreturns: c_int) -> c_int;
@@ -240,6 +260,17 @@ pub fn make_error(additional_message: String, check_box_error_last:bool) -> io::
 
 
 
+/// message of last tarantool error without backtrace
+pub fn last_box_error_message() -> Option<String> {
+    unsafe {
+        let box_error = box_error_last();
+        if box_error.is_null() {
+            return None;
+        }
+        Some(CStr::from_ptr(box_error_message(box_error)).to_string_lossy().into_owned())
+    }
+}
+
 pub fn make_trace() -> String {
     let current_backtrace = backtrace::Backtrace::new();
     current_backtrace.frames().iter().map(|frame|{
@@ -273,8 +304,7 @@ pub fn map_err_to_io<E>(e: E) -> io::Error
     where E: Into<Box<dyn error::Error + Send + Sync>>
 {
 //    error!("Error! {:?}", e.into());
-    println!("Error {:?}", e.into());
-    io::Error::new(io::ErrorKind::Other, "")
+    io::Error::new(io::ErrorKind::Other, e)
 }
 
 
@@ -0,0 +1,138 @@
+use serde::de::DeserializeOwned;
+use std::io;
+use std::marker::PhantomData;
+use crate::tarantool::api::*;
+
+/// Adapters over iterators of tarantool tuples, rows are decoded lazily on next()
+///
+/// # Examples
+///
+/// for row in tarantool.index_iterator(TEST_SPACE, PRIMARY_INDEX, IteratorType::GE, &(1, ))?.decode_fields::<(u32, String)>(&[0, 1]) {
+///     let (id, name) = row?;
+/// }
+///
+pub trait DecodableIterator<'ctx>: Iterator<Item=io::Result<TarantoolTuple<'ctx>>> + Sized {
+    /// decode every row as T
+    fn typed<T>(self) -> TypedIterator<Self, T>
+        where T: DeserializeOwned
+    {
+        TypedIterator { inner: self, phantom: PhantomData }
+    }
+
+    /// decode every row as T with decode_tolerant, rows written by older or newer version of T don't fail
+    fn typed_tolerant<T>(self) -> TolerantIterator<Self, T>
+        where T: DeserializeOwned
+    {
+        TolerantIterator { inner: self, phantom: PhantomData }
+    }
+
+    /// decode only listed fields of every row as T (tuple or struct with fields in listed order)
+    fn decode_fields<T>(self, indexes: &[u32]) -> DecodeFieldsIterator<Self, T>
+        where T: DeserializeOwned
+    {
+        DecodeFieldsIterator { inner: self, indexes: indexes.to_vec(), buf: Vec::new(), phantom: PhantomData }
+    }
+
+    /// skip rows without decoding them, only field with given index is decoded to check predicate
+    fn filter_field<V, F>(self, index: u32, predicate: F) -> FilterFieldIterator<Self, V, F>
+        where V: DeserializeOwned,
+              F: FnMut(&V) -> bool
+    {
+        FilterFieldIterator { inner: self, index, predicate, phantom: PhantomData }
+    }
+
+    /// skip rows not matching predicate, checked on raw msgpack so only matching rows are decoded further
+    fn filter_by(self, predicate: Predicate) -> PredicateIterator<Self> {
+        PredicateIterator::new(self, predicate)
+    }
+}
+
+impl<'ctx, I> DecodableIterator<'ctx> for I where I: Iterator<Item=io::Result<TarantoolTuple<'ctx>>> {}
+
+#[derive(Debug)]
+pub struct TypedIterator<I, T> {
+    inner: I,
+    phantom: PhantomData<T>,
+}
+
+impl<'ctx, I, T> Iterator for TypedIterator<I, T>
+    where I: Iterator<Item=io::Result<TarantoolTuple<'ctx>>>,
+          T: DeserializeOwned
+{
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|row| row.and_then(|row| row.decode()))
+    }
+}
+
+#[derive(Debug)]
+pub struct TolerantIterator<I, T> {
+    inner: I,
+    phantom: PhantomData<T>,
+}
+
+impl<'ctx, I, T> Iterator for TolerantIterator<I, T>
+    where I: Iterator<Item=io::Result<TarantoolTuple<'ctx>>>,
+          T: DeserializeOwned
+{
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|row| row.and_then(|row| row.decode_tolerant()))
+    }
+}
+
+#[derive(Debug)]
+pub struct DecodeFieldsIterator<I, T> {
+    inner: I,
+    indexes: Vec<u32>,
+    //buffer reused between rows
+    buf: Vec<u8>,
+    phantom: PhantomData<T>,
+}
+
+impl<'ctx, I, T> Iterator for DecodeFieldsIterator<I, T>
+    where I: Iterator<Item=io::Result<TarantoolTuple<'ctx>>>,
+          T: DeserializeOwned
+{
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let row = match self.inner.next()? {
+            Ok(row) => row,
+            Err(e) => return Some(Err(e)),
+        };
+        Some(row.copy_fields_to_buf(&self.indexes, &mut self.buf)
+            .and_then(|_| decode_serde(&self.buf[..])))
+    }
+}
+
+pub struct FilterFieldIterator<I, V, F> {
+    inner: I,
+    index: u32,
+    predicate: F,
+    phantom: PhantomData<V>,
+}
+
+impl<'ctx, I, V, F> Iterator for FilterFieldIterator<I, V, F>
+    where I: Iterator<Item=io::Result<TarantoolTuple<'ctx>>>,
+          V: DeserializeOwned,
+          F: FnMut(&V) -> bool
+{
+    type Item = io::Result<TarantoolTuple<'ctx>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let row = match self.inner.next()? {
+                Ok(row) => row,
+                Err(e) => return Some(Err(e)),
+            };
+            match row.decode_field::<V>(self.index) {
+                Ok(ref value) if (self.predicate)(value) => return Some(Ok(row)),
+                Ok(_) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::ffi::CString;
+use std::io;
+use std::mem;
+use std::os::raw::c_char;
+use std::ptr;
+use std::slice;
+use crate::tarantool::api::*;
+use crate::tarantool::internal::*;
+use crate::tarantool::msgpack::*;
+
+/// box_key_part_def_t of module.h
+#[repr(C)]
+struct KeyPartDef {
+    fieldno: u32,
+    flags: u32,
+    field_type: *const c_char,
+    collation: *const c_char,
+    path: *const c_char,
+    _padding: [u8; 64 - 32],
+}
+
+const KEY_PART_IS_NULLABLE: u32 = 1;
+
+type KeyDefNewV2Fn = unsafe extern "C" fn(*const KeyPartDef, u32) -> *const u8;
+type RegionUsedFn = unsafe extern "C" fn() -> usize;
+type RegionTruncateFn = unsafe extern "C" fn(usize);
+
+unsafe fn server_fn(name: &[u8]) -> *mut libc::c_void {
+    libc::dlsym(libc::RTLD_DEFAULT, name.as_ptr() as *const c_char)
+}
+
+lazy_static! {
+    /// box_key_def_new_v2 (collations, nullable parts and paths) is exported only by newer servers
+    static ref KEY_DEF_NEW_V2: Option<KeyDefNewV2Fn> = unsafe {
+        let ptr = server_fn(b"box_key_def_new_v2\0");
+        if ptr.is_null() { None } else { Some(mem::transmute::<*mut libc::c_void, KeyDefNewV2Fn>(ptr)) }
+    };
+    /// extracted keys are allocated on box region, they are freed right after copy if server allows
+    static ref REGION_USED: Option<(RegionUsedFn, RegionTruncateFn)> = unsafe {
+        let used = server_fn(b"box_region_used\0");
+        let truncate = server_fn(b"box_region_truncate\0");
+        if used.is_null() || truncate.is_null() {
+            None
+        } else {
+            Some((mem::transmute::<*mut libc::c_void, RegionUsedFn>(used), mem::transmute::<*mut libc::c_void, RegionTruncateFn>(truncate)))
+        }
+    };
+}
+
+fn c_string(value: &str) -> io::Result<CString> {
+    CString::new(value).map_err(|e| make_error(format!("Invalid key part option {:?}! {}", value, e), false))
+}
+
+/// Key definition made of index parts, compares tuples and keys with server code, collations included
+///
+/// tuples must have fields of types given in parts, like tuples stored in the space of the index
+///
+/// # Examples
+///
+/// let key_def = tarantool.key_def(TEST_SPACE, SECONDARY_INDEX)?;
+/// rows.sort_by(|a, b| key_def.compare(&a.as_tuple(), &b.as_tuple()));
+/// rows.dedup_by(|a, b| key_def.compare(&a.as_tuple(), &b.as_tuple()) == Ordering::Equal);
+///
+#[derive(Debug)]
+pub struct KeyDef {
+    ptr: *const u8,
+    parts: Vec<IndexPart>,
+    /// space and index ids if key def is made of index, keys are extracted by server then
+    index: Option<(u32, u32)>,
+}
+
+impl KeyDef {
+    pub fn new(parts: &[IndexPart]) -> io::Result<KeyDef> {
+        if parts.is_empty() {
+            return Err(make_error("Key def needs at least one part!".to_string(), false));
+        }
+        let ptr = match *KEY_DEF_NEW_V2 {
+            Some(key_def_new_v2) => {
+                let mut strings = Vec::new();
+                let mut defs = Vec::with_capacity(parts.len());
+                for part in parts {
+                    let field_type = c_string(&part.type_name)?;
+                    let collation = part.collation.as_deref().map(c_string).transpose()?;
+                    let path = part.path.as_deref().map(c_string).transpose()?;
+                    defs.push(KeyPartDef {
+                        fieldno: part.field,
+                        flags: if part.is_nullable { KEY_PART_IS_NULLABLE } else { 0 },
+                        field_type: field_type.as_ptr(),
+                        collation: collation.as_ref().map_or(ptr::null(), |v| v.as_ptr()),
+                        path: path.as_ref().map_or(ptr::null(), |v| v.as_ptr()),
+                        _padding: [0; 64 - 32],
+                    });
+                    strings.push((field_type, collation, path));
+                }
+                unsafe { key_def_new_v2(defs.as_ptr(), defs.len() as u32) }
+            }
+            None => {
+                let mut fields = Vec::with_capacity(parts.len());
+                let mut types = Vec::with_capacity(parts.len());
+                for part in parts {
+                    if part.collation.is_some() || part.path.is_some() || part.is_nullable {
+                        return Err(make_error(format!("Server can't make key def with collation, path or nullable part! field={}", part.field), false));
+                    }
+                    let field_type = part.field_type()
+                        .ok_or_else(|| make_error(format!("Unknown key part type {:?}!", part.type_name), false))?;
+                    fields.push(part.field);
+                    types.push(field_type.server_code()?);
+                }
+                unsafe { box_key_def_new(fields.as_ptr(), types.as_ptr(), parts.len() as u32) }
+            }
+        };
+        if ptr.is_null() {
+            return make_error_result("error on create key def!".to_string());
+        }
+        Ok(KeyDef { ptr, parts: parts.to_vec(), index: None })
+    }
+
+    pub fn parts(&self) -> &[IndexPart] {
+        &self.parts
+    }
+
+    /// order of tuples in index
+    pub fn compare(&self, a: &TarantoolTuple, b: &TarantoolTuple) -> Ordering {
+        unsafe { box_tuple_compare(a.row_data, b.row_data, self.ptr) }.cmp(&0)
+    }
+
+    /// order of tuple and key (array of key parts), key may be shorter than key def
+    pub fn compare_with_key<K: Serialize>(&self, tuple: &TarantoolTuple, key: &K) -> io::Result<Ordering> {
+        let mut key_data = Vec::new();
+        serialize_to_vec(&mut key_data, key)?;
+        self.compare_with_key_data(tuple, &key_data)
+    }
+
+    /// same as compare_with_key for encoded key, key must be one msgpack array of up to parts count values
+    pub fn compare_with_key_data(&self, tuple: &TarantoolTuple, key_data: &[u8]) -> io::Result<Ordering> {
+        let (count, _) = read_array_header(key_data)?;
+        if value_len(key_data)? != key_data.len() {
+            return Err(make_error("Key data must be one msgpack array!".to_string(), false));
+        }
+        if count as usize > self.parts.len() {
+            return Err(make_error(format!("Key has {} parts, key def has only {}!", count, self.parts.len()), false));
+        }
+        Ok(unsafe { box_tuple_compare_with_key(tuple.row_data, key_data.as_ptr(), self.ptr) }.cmp(&0))
+    }
+
+    /// msgpack array of key parts of the tuple
+    pub fn extract_key(&self, tuple: &TarantoolTuple) -> io::Result<Vec<u8>> {
+        let (space_id, index_id) = match self.index {
+            Some(index) => index,
+            None => {
+                if let Some(part) = self.parts.iter().find(|part| part.path.is_some()) {
+                    return Err(make_error(format!("Key with path can be extracted only by index key def! field={}", part.field), false));
+                }
+                let mut key = Vec::new();
+                tuple.copy_fields_to_buf(&self.parts.iter().map(|part| part.field).collect::<Vec<_>>(), &mut key)?;
+                return Ok(key);
+            }
+        };
+        unsafe {
+            let region_used = REGION_USED.map(|(used, _)| used());
+            let mut key_size: u32 = 0;
+            let key = box_tuple_extract_key(tuple.row_data, space_id, index_id, &mut key_size);
+            if key.is_null() {
+                return make_error_result(format!("error on extract key! space id={} index id={}", space_id, index_id));
+            }
+            let res = slice::from_raw_parts(key, key_size as usize).to_vec();
+            if let (Some((_, truncate)), Some(used)) = (*REGION_USED, region_used) {
+                truncate(used);
+            }
+            Ok(res)
+        }
+    }
+
+    /// extract and decode key of the tuple
+    pub fn extract_key_as<T: DeserializeOwned>(&self, tuple: &TarantoolTuple) -> io::Result<T> {
+        decode_serde(&self.extract_key(tuple)?[..])
+    }
+}
+
+impl Drop for KeyDef {
+    fn drop(&mut self) {
+        unsafe {
+            box_key_def_delete(self.ptr);
+        }
+    }
+}
+
+/// Tuple ordered by key def, for BTreeSet, BinaryHeap, sort and dedup of tuples in Rust
+///
+/// # Examples
+///
+/// let unique: BTreeSet<KeyOrdered> = rows.into_iter().map(|row| KeyOrdered::new(&key_def, row)).collect();
+///
+#[derive(Debug, Clone)]
+pub struct KeyOrdered<'k> {
+    key_def: &'k KeyDef,
+    pub tuple: OwnedTuple,
+}
+
+impl<'k> KeyOrdered<'k> {
+    pub fn new(key_def: &'k KeyDef, tuple: OwnedTuple) -> KeyOrdered<'k> {
+        KeyOrdered { key_def, tuple }
+    }
+
+    pub fn into_tuple(self) -> OwnedTuple {
+        self.tuple
+    }
+}
+
+impl<'k> PartialEq for KeyOrdered<'k> {
+    fn eq(&self, other: &KeyOrdered<'k>) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<'k> Eq for KeyOrdered<'k> {}
+
+impl<'k> PartialOrd for KeyOrdered<'k> {
+    fn partial_cmp(&self, other: &KeyOrdered<'k>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'k> Ord for KeyOrdered<'k> {
+    fn cmp(&self, other: &KeyOrdered<'k>) -> Ordering {
+        self.key_def.compare(&self.tuple.as_tuple(), &other.tuple.as_tuple())
+    }
+}
+
+impl TarantoolContext {
+    /// key def with parts of index
+    pub fn key_def<S, S1>(&self, space_name: S, index_name: S1) -> io::Result<KeyDef>
+        where S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        let info = self.index_info(space_name, index_name)?;
+        let mut key_def = KeyDef::new(&info.parts)?;
+        key_def.index = Some((info.space_id, info.id));
+        Ok(key_def)
+    }
+}
@@ -0,0 +1,208 @@
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use crate::tarantool::api::*;
+
+/// Point of N dimensions, key for RTREE index (NEIGHBOR iterator) or value of RTREE field
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Point<const N: usize>(pub [f64; N]);
+
+/// Rectangle (box) of N dimensions given by two opposite corners, key for RTREE index
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Rect<const N: usize> {
+    pub min: [f64; N],
+    pub max: [f64; N],
+}
+
+impl<const N: usize> Rect<N> {
+    pub fn new(min: [f64; N], max: [f64; N]) -> Rect<N> {
+        Rect { min, max }
+    }
+}
+
+impl<const N: usize> From<Point<N>> for Rect<N> {
+    fn from(point: Point<N>) -> Rect<N> {
+        Rect { min: point.0, max: point.0 }
+    }
+}
+
+/// serialized as flat sequence of coordinates, like {x, y} in lua
+impl<const N: usize> Serialize for Point<N> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let mut seq = serializer.serialize_seq(Some(N))?;
+        for coord in self.0.iter() {
+            seq.serialize_element(coord)?;
+        }
+        seq.end()
+    }
+}
+
+/// serialized as flat sequence of coordinates, like {x1, y1, x2, y2} in lua
+impl<const N: usize> Serialize for Rect<N> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let mut seq = serializer.serialize_seq(Some(2 * N))?;
+        for coord in self.min.iter().chain(self.max.iter()) {
+            seq.serialize_element(coord)?;
+        }
+        seq.end()
+    }
+}
+
+struct CoordsVisitor<const C: usize> {
+    phantom: PhantomData<[f64; C]>,
+}
+
+impl<'de, const C: usize> Visitor<'de> for CoordsVisitor<C> {
+    type Value = [f64; C];
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "sequence of {} coordinates", C)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error> where A: SeqAccess<'de> {
+        let mut coords = [0f64; C];
+        for (i, coord) in coords.iter_mut().enumerate() {
+            *coord = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<f64>()?.is_some() {
+            return Err(de::Error::invalid_length(C + 1, &self));
+        }
+        Ok(coords)
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for Point<N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        deserializer.deserialize_seq(CoordsVisitor::<N> { phantom: PhantomData }).map(Point)
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for Rect<N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        //RTREE field may hold point as well as rectangle
+        let coords: Vec<f64> = Deserialize::deserialize(deserializer)?;
+        let mut rect = Rect { min: [0f64; N], max: [0f64; N] };
+        if coords.len() == N {
+            rect.min.copy_from_slice(&coords);
+            rect.max.copy_from_slice(&coords);
+        } else if coords.len() == 2 * N {
+            rect.min.copy_from_slice(&coords[..N]);
+            rect.max.copy_from_slice(&coords[N..]);
+        } else {
+            return Err(de::Error::invalid_length(coords.len(), &format!("{} or {} coordinates", N, 2 * N).as_str()));
+        }
+        Ok(rect)
+    }
+}
+
+/// Search in RTREE index
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SpatialQuery<const N: usize> {
+    All,
+    /// boxes equal to the rectangle
+    Equals(Rect<N>),
+    /// boxes which contain the rectangle
+    Contains(Rect<N>),
+    /// boxes which strictly contain the rectangle
+    StrictlyContains(Rect<N>),
+    /// boxes which are inside the rectangle
+    Within(Rect<N>),
+    /// boxes which are strictly inside the rectangle
+    StrictlyWithin(Rect<N>),
+    /// boxes which overlap the rectangle
+    Overlaps(Rect<N>),
+    /// all boxes ordered by distance from the point
+    Neighbor(Point<N>),
+}
+
+impl<const N: usize> SpatialQuery<N> {
+    pub fn iterator_type(&self) -> IteratorType {
+        match self {
+            SpatialQuery::All => IteratorType::ALL,
+            SpatialQuery::Equals(_) => IteratorType::EQ,
+            SpatialQuery::Contains(_) => IteratorType::GE,
+            SpatialQuery::StrictlyContains(_) => IteratorType::GT,
+            SpatialQuery::Within(_) => IteratorType::LE,
+            SpatialQuery::StrictlyWithin(_) => IteratorType::LT,
+            SpatialQuery::Overlaps(_) => IteratorType::Overlaps,
+            SpatialQuery::Neighbor(_) => IteratorType::Neighbor,
+        }
+    }
+
+    /// key as msgpack array of coordinates
+    pub fn key_data(&self) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        match self {
+            SpatialQuery::All => serialize_to_buf_mut(&mut buf, &Vec::<f64>::new())?,
+            SpatialQuery::Neighbor(point) => serialize_to_buf_mut(&mut buf, point)?,
+            SpatialQuery::Equals(rect) |
+            SpatialQuery::Contains(rect) |
+            SpatialQuery::StrictlyContains(rect) |
+            SpatialQuery::Within(rect) |
+            SpatialQuery::StrictlyWithin(rect) |
+            SpatialQuery::Overlaps(rect) => serialize_to_buf_mut(&mut buf, rect)?,
+        }
+        Ok(buf)
+    }
+}
+
+/// Search in BITSET index, key is bitmask
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BitsetQuery {
+    All,
+    /// value is equal to mask
+    Equals(u64),
+    /// all bits of mask are set in value
+    AllSet(u64),
+    /// any bit of mask is set in value
+    AnySet(u64),
+    /// no bit of mask is set in value
+    AllNotSet(u64),
+}
+
+impl BitsetQuery {
+    pub fn iterator_type(&self) -> IteratorType {
+        match self {
+            BitsetQuery::All => IteratorType::ALL,
+            BitsetQuery::Equals(_) => IteratorType::EQ,
+            BitsetQuery::AllSet(_) => IteratorType::BitsAllSet,
+            BitsetQuery::AnySet(_) => IteratorType::BitsAnySet,
+            BitsetQuery::AllNotSet(_) => IteratorType::BitsAllNotSet,
+        }
+    }
+
+    /// key as msgpack array with one bitmask
+    pub fn key_data(&self) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        match self {
+            BitsetQuery::All => serialize_to_buf_mut(&mut buf, &Vec::<u64>::new())?,
+            BitsetQuery::Equals(mask) |
+            BitsetQuery::AllSet(mask) |
+            BitsetQuery::AnySet(mask) |
+            BitsetQuery::AllNotSet(mask) => serialize_to_buf_mut(&mut buf, &(mask, ))?,
+        }
+        Ok(buf)
+    }
+}
+
+impl TarantoolContext {
+    /// search in RTREE index
+    pub fn rtree_iterator<S, S1, const N: usize>(&self, space_name: S, index_name: S1, query: &SpatialQuery<N>) -> io::Result<TarantoolIterator<'_>>
+        where S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        let (space_id, index_id) = self.get_space_and_index_id(&space_name, &index_name)?;
+        self.index_iterator_data(space_id, index_id, query.iterator_type(), query.key_data()?)
+    }
+
+    /// search in BITSET index
+    pub fn bitset_iterator<S, S1>(&self, space_name: S, index_name: S1, query: &BitsetQuery) -> io::Result<TarantoolIterator<'_>>
+        where S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        let (space_id, index_id) = self.get_space_and_index_id(&space_name, &index_name)?;
+        self.index_iterator_data(space_id, index_id, query.iterator_type(), query.key_data()?)
+    }
+}
@@ -0,0 +1,93 @@
+use rmpv::Value;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io;
+use std::ops::Range;
+use std::ptr;
+use crate::tarantool::api::*;
+use crate::tarantool::internal::*;
+use crate::tarantool::tuple::OwnedTuple;
+
+/// How index_get_many runs lookups, results are always in order of given keys
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct GetManyOptions {
+    /// look up equal keys once
+    pub dedupe: bool,
+    /// look up keys sorted by their msgpack values, it is not index order: collations and field types
+    /// of index parts are not taken into account
+    pub sort: bool,
+}
+
+impl TarantoolContext {
+    /// get rows by many keys, space and index ids are resolved once and all keys are serialized into one buffer
+    ///
+    /// every row is referenced right after the lookup, it stays valid while the next keys are looked up
+    pub fn index_get_many_raw<K, S, S1>(&self, space_name: S, index_name: S1, keys: &[K], options: &GetManyOptions) -> io::Result<Vec<Option<OwnedTuple>>>
+        where K: Serialize,
+              S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        let (space_id, index_id) = self.get_space_and_index_id(&space_name, &index_name)?;
+        let mut buf = Vec::new();
+        let mut ranges: Vec<Range<usize>> = Vec::with_capacity(keys.len());
+        for key in keys {
+            let start = buf.len();
+            serialize_to_buf_mut(&mut buf, key)?;
+            ranges.push(start..buf.len());
+        }
+
+        let mut order: Vec<usize> = (0..keys.len()).collect();
+        if options.sort {
+            let decoded = ranges.iter()
+                .map(|range| decode_serde(&buf[range.clone()]))
+                .collect::<io::Result<Vec<Vec<Value>>>>()?;
+            order.sort_by(|a, b| compare_keys(&decoded[*a], &decoded[*b]));
+        }
+
+        let mut rows: Vec<Option<OwnedTuple>> = vec![None; keys.len()];
+        let mut found: HashMap<&[u8], Option<OwnedTuple>> = HashMap::new();
+        for i in order {
+            let key = &buf[ranges[i].clone()];
+            if options.dedupe {
+                if let Some(row) = found.get(key) {
+                    rows[i] = row.clone();
+                    continue;
+                }
+            }
+            let mut row: *mut u8 = ptr::null_mut();
+            unsafe {
+                if box_index_get(space_id, index_id, key.as_ptr(), key.as_ptr().add(key.len()), &mut row) == -1 {
+                    return make_error_result(format!("error on get data! space id={} index id={}", space_id, index_id));
+                }
+            }
+            let row = if row.is_null() { None } else { Some(OwnedTuple::from_ptr(row)) };
+            if options.dedupe {
+                found.insert(key, row.clone());
+            }
+            rows[i] = row;
+        }
+        Ok(rows)
+    }
+
+    /// get and decode rows by many keys, None for keys without row
+    ///
+    /// # Examples
+    ///
+    /// let countries: Vec<Option<CountryData>> = tarantool.index_get_many(COUNTRY_SPACE, COUNTRY_INDEX, &codes, &GetManyOptions::default())?;
+    ///
+    pub fn index_get_many<T, K, S, S1>(&self, space_name: S, index_name: S1, keys: &[K], options: &GetManyOptions) -> io::Result<Vec<Option<T>>>
+        where T: DeserializeOwned,
+              K: Serialize,
+              S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        self.index_get_many_raw(space_name, index_name, keys, options)?
+            .iter()
+            .map(|row| match row {
+                Some(row) => row.as_tuple().decode().map(Some),
+                None => Ok(None),
+            })
+            .collect()
+    }
+}
@@ -36,6 +36,68 @@ macro_rules! tarantool_register_stored_procs {
     };
 }
 
+/// export init_migrations_ffi which applies migrations listed by given function at module init,
+/// call it from init script after box.cfg and stop if it returns -1 (box.error.last() has the reason)
+///
+/// stored procedure run_migrations applies them later and returns applied versions
+///
+/// # Examples
+///
+/// ffi.cdef[[ int init_migrations_ffi(); ]]
+/// if rust.init_migrations_ffi() ~= 0 then error(box.error.last()) end
+///
+#[macro_export]
+macro_rules! tarantool_register_migrations {
+    ($migrations_fn:ident) => {
+        #[no_mangle]
+        pub extern "C" fn init_migrations_ffi() -> std::os::raw::c_int {
+            tarantool_rust_api::tarantool::migrations::run_migrations_ffi(&$migrations_fn())
+        }
+
+        #[no_mangle]
+        pub fn run_migrations(context: tarantool_rust_api::tarantool::api::StoredProcCtx,
+                              args: tarantool_rust_api::tarantool::api::StoredProcArgs,
+                              args_end: tarantool_rust_api::tarantool::api::StoredProcArgsEnd ) -> std::os::raw::c_int  {
+            let tarantool = TarantoolContext::new(context, args, args_end);
+            return tarantool.return_tuple(tarantool.run_migrations(&$migrations_fn()), None);
+        }
+    };
+}
+
+/// write struct as msgpack map with field names instead of array, other structs stay arrays
+///
+/// struct derives Serialize and Deserialize with #[serde(remote = "Self")], serde attributes like rename still apply
+///
+/// # Examples
+///
+/// #[derive(Serialize, Deserialize, Clone, Debug)]
+/// #[serde(remote = "Self")]
+/// pub struct TestStruct {
+///     pub a: u64,
+///     pub b: Value,
+/// }
+///
+/// tarantool_struct_as_map!(TestStruct);
+///
+#[macro_export]
+macro_rules! tarantool_struct_as_map {
+    ($( $name:ident ),* ) => {
+        $(
+            impl serde::Serialize for $name {
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
+                    $name::serialize(self, tarantool_rust_api::tarantool::api::StructAsMap(serializer))
+                }
+            }
+
+            impl<'de> serde::Deserialize<'de> for $name {
+                fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: serde::Deserializer<'de> {
+                    $name::deserialize(deserializer)
+                }
+            }
+        )*
+    };
+}
+
 //tarantool_register_stored_procs! {
 //    test_index_get => test_index_get_impl,
 //    test_replace => test_replace_impl
@@ -0,0 +1,172 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::io;
+use crate::tarantool::api::*;
+
+/// current tuple of one source, heap gives the least tuple first, equal tuples are taken in order of sources
+struct Head<'k> {
+    tuple: OwnedTuple,
+    source: usize,
+    key_def: &'k KeyDef,
+    reverse: bool,
+}
+
+impl<'k> Head<'k> {
+    fn key_cmp(&self, other: &Head<'k>) -> Ordering {
+        let ord = self.key_def.compare(&self.tuple.as_tuple(), &other.tuple.as_tuple());
+        if self.reverse { ord.reverse() } else { ord }
+    }
+}
+
+impl<'k> PartialEq for Head<'k> {
+    fn eq(&self, other: &Head<'k>) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<'k> Eq for Head<'k> {}
+
+impl<'k> PartialOrd for Head<'k> {
+    fn partial_cmp(&self, other: &Head<'k>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'k> Ord for Head<'k> {
+    fn cmp(&self, other: &Head<'k>) -> Ordering {
+        //BinaryHeap is max heap
+        self.key_cmp(other).then(self.source.cmp(&other.source)).reverse()
+    }
+}
+
+/// K-way merge of iterators sorted by key def into one sorted iterator, like merger module of tarantool
+///
+/// every source must be sorted in order of key def (descending for reverse()), tuples are kept referenced
+/// so sources may be iterators of different spaces
+///
+/// # Examples
+///
+/// let key_def = tarantool.key_def("orders_1", "created")?;
+/// let sources = vec![
+///     tarantool.index_iterator("orders_1", "created", IteratorType::GE, &(from, ))?,
+///     tarantool.index_iterator("orders_2", "created", IteratorType::GE, &(from, ))?,
+/// ];
+/// for row in Merger::new(&key_def, sources).dedup().limit(100) {
+///     let order: Order = row?.as_tuple().decode()?;
+/// }
+///
+pub struct Merger<'k, I> {
+    key_def: &'k KeyDef,
+    sources: Vec<Option<I>>,
+    heap: BinaryHeap<Head<'k>>,
+    /// sources before this one have given their first tuple
+    started: usize,
+    /// error of source which is returned after already taken tuple
+    error: Option<io::Error>,
+    reverse: bool,
+    dedup: bool,
+    limit: Option<usize>,
+    count: usize,
+    last: Option<OwnedTuple>,
+}
+
+impl<'k, 'ctx, I> Merger<'k, I> where I: Iterator<Item=io::Result<TarantoolTuple<'ctx>>> {
+    pub fn new<S>(key_def: &'k KeyDef, sources: S) -> Merger<'k, I>
+        where S: IntoIterator<Item=I>
+    {
+        Merger {
+            key_def,
+            sources: sources.into_iter().map(Some).collect(),
+            heap: BinaryHeap::new(),
+            started: 0,
+            error: None,
+            reverse: false,
+            dedup: false,
+            limit: None,
+            count: 0,
+            last: None,
+        }
+    }
+
+    /// sources are sorted descending, e.g. LE or REQ iterators
+    pub fn reverse(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
+    /// skip tuples with key equal to key of previous tuple, first of equal tuples (in order of sources) is kept
+    pub fn dedup(mut self) -> Self {
+        self.dedup = true;
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// take next tuple of the source to heap, source is dropped when it ends or fails
+    fn fetch(&mut self, source: usize) -> io::Result<()> {
+        let res = match self.sources[source].as_mut() {
+            Some(iter) => iter.next(),
+            None => return Ok(()),
+        };
+        match res {
+            Some(Ok(row)) => {
+                self.heap.push(Head { tuple: row.to_owned_tuple(), source, key_def: self.key_def, reverse: self.reverse });
+                Ok(())
+            }
+            Some(Err(e)) => {
+                self.sources[source] = None;
+                Err(e)
+            }
+            None => {
+                self.sources[source] = None;
+                Ok(())
+            }
+        }
+    }
+
+    fn is_duplicate(&self, tuple: &OwnedTuple) -> bool {
+        match self.last {
+            Some(ref last) if self.dedup => self.key_def.compare(&last.as_tuple(), &tuple.as_tuple()) == Ordering::Equal,
+            _ => false,
+        }
+    }
+}
+
+impl<'k, 'ctx, I> Iterator for Merger<'k, I> where I: Iterator<Item=io::Result<TarantoolTuple<'ctx>>> {
+    type Item = io::Result<OwnedTuple>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.limit.is_some_and(|limit| self.count >= limit) {
+            return None;
+        }
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+        while self.started < self.sources.len() {
+            self.started += 1;
+            if let Err(e) = self.fetch(self.started - 1) {
+                return Some(Err(e));
+            }
+        }
+        loop {
+            let head = self.heap.pop()?;
+            if let Err(e) = self.fetch(head.source) {
+                self.error = Some(e);
+            }
+            if self.is_duplicate(&head.tuple) {
+                if let Some(e) = self.error.take() {
+                    return Some(Err(e));
+                }
+                continue;
+            }
+            self.count += 1;
+            if self.dedup {
+                self.last = Some(head.tuple.clone());
+            }
+            return Some(Ok(head.tuple));
+        }
+    }
+}
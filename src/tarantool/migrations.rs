@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::io;
+use std::os::raw::{c_int, c_uchar};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::tarantool::api::*;
+use crate::tarantool::internal::*;
+
+/// space with records of applied migrations: [version, name, applied at (unix time)]
+pub const MIGRATIONS_SPACE: &str = "_rust_migrations";
+const MIGRATIONS_INDEX: &str = "primary";
+
+pub type MigrationFn = fn(&TarantoolContext) -> io::Result<()>;
+
+/// Versioned step of schema migration, DDL and data backfill written with CRUD api
+///
+/// step made by new and its record are committed in one transaction, so it must not yield;
+/// step made by new_ddl runs outside of transaction and is recorded after it succeeded, it must be
+/// idempotent (if_not_exists options etc.) because it runs again if it failed before the record was written
+///
+/// # Examples
+///
+/// fn migrations() -> Vec<Migration> {
+///     vec![
+///         Migration::new_ddl(1, "create users", create_users),
+///         Migration::new(2, "backfill user names", backfill_user_names),
+///     ]
+/// }
+///
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// unique, pending migrations are applied in ascending order of versions
+    pub version: u32,
+    pub name: &'static str,
+    pub up: MigrationFn,
+    /// step and its record are committed in one transaction
+    pub transactional: bool,
+}
+
+impl Migration {
+    pub fn new(version: u32, name: &'static str, up: MigrationFn) -> Migration {
+        Migration { version, name, up, transactional: true }
+    }
+
+    /// step which can't run in transaction: DDL on 1.x servers, DDL which yields like index build
+    /// over existing rows, several DDL statements
+    pub fn new_ddl(version: u32, name: &'static str, up: MigrationFn) -> Migration {
+        Migration { version, name, up, transactional: false }
+    }
+}
+
+thread_local! {
+    /// fibers which run migrations at the same time wait for each other
+    static MIGRATIONS_LATCH: *mut c_uchar = unsafe { box_latch_new() };
+}
+
+struct LatchGuard(*mut c_uchar);
+
+impl LatchGuard {
+    fn lock() -> LatchGuard {
+        let latch = MIGRATIONS_LATCH.with(|latch| *latch);
+        unsafe {
+            box_latch_lock(latch);
+        }
+        LatchGuard(latch)
+    }
+}
+
+impl Drop for LatchGuard {
+    fn drop(&mut self) {
+        unsafe {
+            box_latch_unlock(self.0);
+        }
+    }
+}
+
+fn now_secs() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|v| v.as_secs_f64()).unwrap_or(0f64)
+}
+
+impl TarantoolContext {
+    /// versions and names of applied migrations
+    pub fn applied_migrations(&self) -> io::Result<Vec<(u32, String)>> {
+        let mut applied = Vec::new();
+        for row in self.index_iterator_all(MIGRATIONS_SPACE, MIGRATIONS_INDEX)? {
+            applied.push(row?.decode_fields(&[0, 1])?);
+        }
+        Ok(applied)
+    }
+
+    fn create_migrations_space(&self) -> io::Result<()> {
+        self.create_space(MIGRATIONS_SPACE, &SpaceOptions {
+            format: vec![FieldInfo::new("version", "unsigned"), FieldInfo::new("name", "string"), FieldInfo::new("applied_at", "number")],
+            if_not_exists: true,
+            ..Default::default()
+        })?;
+        let mut options = IndexOptions::new(IndexType::Tree, vec![IndexPart::new(0, "unsigned")]);
+        options.if_not_exists = true;
+        self.create_index(MIGRATIONS_SPACE, MIGRATIONS_INDEX, &options)
+    }
+
+    fn apply_migration(&self, migration: &Migration) -> io::Result<()> {
+        if !migration.transactional {
+            (migration.up)(self)?;
+            return self.insert(MIGRATIONS_SPACE, &(migration.version, migration.name, now_secs()));
+        }
+        self.txn_begin()?;
+        let res = (migration.up)(self).and_then(|_| self.insert(MIGRATIONS_SPACE, &(migration.version, migration.name, now_secs())));
+        match res {
+            Ok(_) => self.txn_commit(),
+            Err(e) => {
+                self.txn_rollback()?;
+                Err(e)
+            }
+        }
+    }
+
+    /// apply pending migrations, returns versions applied by this call
+    ///
+    /// refuses to run if a migration recorded in _rust_migrations is missing from the list or was renamed,
+    /// concurrent call waits until this one is finished
+    pub fn run_migrations(&self, migrations: &[Migration]) -> io::Result<Vec<u32>> {
+        let _guard = LatchGuard::lock();
+        let mut migrations = migrations.to_vec();
+        migrations.sort_by_key(|migration| migration.version);
+        if let Some(pair) = migrations.windows(2).find(|pair| pair[0].version == pair[1].version) {
+            return Err(make_error(format!("Duplicate migration version {}!", pair[0].version), false));
+        }
+
+        self.create_migrations_space()?;
+        let known: HashMap<u32, &str> = migrations.iter().map(|migration| (migration.version, migration.name)).collect();
+        let applied = self.applied_migrations()?;
+        for (version, name) in applied.iter() {
+            match known.get(version) {
+                Some(known_name) if known_name == name => {}
+                Some(known_name) => return Err(make_error(format!("Migration {} is applied as {:?} but module has {:?}!", version, name, known_name), false)),
+                None => return Err(make_error(format!("Migration {} {:?} is applied but missing in module!", version, name), false)),
+            }
+        }
+
+        let mut done = Vec::new();
+        for migration in migrations.iter().filter(|migration| !applied.iter().any(|(version, _)| *version == migration.version)) {
+            self.apply_migration(migration)
+                .map_err(|e| make_error(format!("Migration {} {:?} failed! {}", migration.version, migration.name, e), false))?;
+            done.push(migration.version);
+        }
+        Ok(done)
+    }
+}
+
+/// run migrations at module init, see tarantool_register_migrations
+///
+/// returns -1 and sets box error if migrations failed
+pub fn run_migrations_ffi(migrations: &[Migration]) -> c_int {
+    match TarantoolContext::new_ffi().run_migrations(migrations) {
+        Ok(_) => 0,
+        Err(e) => {
+            let _ = set_last_error_wrapper(&e.to_string());
+            -1
+        }
+    }
+}
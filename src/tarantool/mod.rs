@@ -1,4 +1,30 @@
 pub mod api;
 pub mod macros;
 mod internal;
-mod dict;
\ No newline at end of file
+mod dict;
+mod msgpack;
+pub mod iter;
+pub mod select;
+pub mod cursor;
+mod yielding;
+pub mod keys;
+pub mod schema;
+mod format;
+mod path;
+pub mod ddl;
+pub mod migrations;
+pub mod batch;
+pub mod lookup;
+mod buffer;
+pub mod encoding;
+pub mod tolerant;
+pub mod ext;
+pub mod dynamic;
+pub mod tuple;
+pub mod keydef;
+pub mod merger;
+pub mod predicate;
+pub mod aggregate;
+pub mod stats;
+pub mod session;
+pub mod push;
@@ -0,0 +1,322 @@
+use rmp::Marker;
+use std::io;
+use crate::tarantool::internal::make_error;
+
+/// msgpack nil, used in place of absent tuple fields
+pub const MSGPACK_NIL: u8 = 0xc0;
+
+fn truncated<T>() -> io::Result<T> {
+    Err(make_error("truncated msgpack data!".to_string(), false))
+}
+
+fn read_be(data: &[u8], pos: usize, size: usize) -> io::Result<usize> {
+    match data.get(pos..pos + size) {
+        Some(bytes) => Ok(bytes.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize)),
+        None => truncated(),
+    }
+}
+
+/// size of encoded array header for given elements count
+pub fn array_header_len(count: u32) -> usize {
+    match count {
+        0..=15 => 1,
+        16..=0xffff => 3,
+        _ => 5,
+    }
+}
+
+/// write array header for given elements count
+pub fn write_array_header(buf: &mut Vec<u8>, count: u32) {
+    match count {
+        0..=15 => buf.push(0x90 | count as u8),
+        16..=0xffff => {
+            buf.push(0xdc);
+            buf.extend_from_slice(&(count as u16).to_be_bytes());
+        }
+        _ => {
+            buf.push(0xdd);
+            buf.extend_from_slice(&count.to_be_bytes());
+        }
+    }
+}
+
+/// read array header, returns elements count and header size
+pub fn read_array_header(data: &[u8]) -> io::Result<(u32, usize)> {
+    match data.first().map(|b| Marker::from_u8(*b)) {
+        Some(Marker::FixArray(n)) => Ok((n as u32, 1)),
+        Some(Marker::Array16) => Ok((read_be(data, 1, 2)? as u32, 3)),
+        Some(Marker::Array32) => Ok((read_be(data, 1, 4)? as u32, 5)),
+        Some(marker) => Err(make_error(format!("msgpack array expected, found {:?}!", marker), false)),
+        None => truncated(),
+    }
+}
+
+/// What follows marker and size field of msgpack value
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Body {
+    /// fixed number of bytes
+    Bytes(usize),
+    /// size field bytes plus extra bytes (type of ext)
+    SizedBytes(usize),
+    /// fixed number of nested values
+    Values(usize),
+    /// size field nested values times multiplier (2 for maps)
+    SizedValues(usize),
+}
+
+/// size of big endian size field after marker and body of value
+pub(crate) fn marker_layout(marker: Marker) -> io::Result<(usize, Body)> {
+    Ok(match marker {
+        Marker::FixPos(_) | Marker::FixNeg(_) | Marker::Null | Marker::True | Marker::False => (0, Body::Bytes(0)),
+        Marker::U8 | Marker::I8 => (0, Body::Bytes(1)),
+        Marker::U16 | Marker::I16 => (0, Body::Bytes(2)),
+        Marker::U32 | Marker::I32 | Marker::F32 => (0, Body::Bytes(4)),
+        Marker::U64 | Marker::I64 | Marker::F64 => (0, Body::Bytes(8)),
+        Marker::FixStr(n) => (0, Body::Bytes(n as usize)),
+        Marker::Str8 | Marker::Bin8 => (1, Body::SizedBytes(0)),
+        Marker::Str16 | Marker::Bin16 => (2, Body::SizedBytes(0)),
+        Marker::Str32 | Marker::Bin32 => (4, Body::SizedBytes(0)),
+        Marker::FixArray(n) => (0, Body::Values(n as usize)),
+        Marker::Array16 => (2, Body::SizedValues(1)),
+        Marker::Array32 => (4, Body::SizedValues(1)),
+        Marker::FixMap(n) => (0, Body::Values(2 * n as usize)),
+        Marker::Map16 => (2, Body::SizedValues(2)),
+        Marker::Map32 => (4, Body::SizedValues(2)),
+        Marker::FixExt1 => (0, Body::Bytes(2)),
+        Marker::FixExt2 => (0, Body::Bytes(3)),
+        Marker::FixExt4 => (0, Body::Bytes(5)),
+        Marker::FixExt8 => (0, Body::Bytes(9)),
+        Marker::FixExt16 => (0, Body::Bytes(17)),
+        Marker::Ext8 => (1, Body::SizedBytes(1)),
+        Marker::Ext16 => (2, Body::SizedBytes(1)),
+        Marker::Ext32 => (4, Body::SizedBytes(1)),
+        Marker::Reserved => return Err(make_error("reserved msgpack marker 0xc1!".to_string(), false)),
+    })
+}
+
+pub(crate) fn is_ext_marker(marker: Marker) -> bool {
+    matches!(marker, Marker::FixExt1 | Marker::FixExt2 | Marker::FixExt4 | Marker::FixExt8 | Marker::FixExt16
+        | Marker::Ext8 | Marker::Ext16 | Marker::Ext32)
+}
+
+/// size in bytes of the first msgpack value in data, nested arrays and maps included
+pub fn value_len(data: &[u8]) -> io::Result<usize> {
+    Ok(scan_value(data)?.0)
+}
+
+/// size of the first msgpack value in data and whether there are ext values in it
+pub(crate) fn scan_value(data: &[u8]) -> io::Result<(usize, bool)> {
+    let mut pos = 0;
+    let mut pending: usize = 1;
+    let mut has_ext = false;
+    while pending > 0 {
+        pending -= 1;
+        let marker = match data.get(pos) {
+            Some(b) => Marker::from_u8(*b),
+            None => return truncated(),
+        };
+        has_ext |= is_ext_marker(marker);
+        let (size_len, body) = marker_layout(marker)?;
+        let size = if size_len > 0 { read_be(data, pos + 1, size_len)? } else { 0 };
+        pos += 1 + size_len;
+        match body {
+            Body::Bytes(n) => pos += n,
+            Body::SizedBytes(extra) => pos += size + extra,
+            Body::Values(n) => pending += n,
+            Body::SizedValues(multiplier) => pending += size * multiplier,
+        }
+    }
+    if pos > data.len() {
+        return truncated();
+    }
+    Ok((pos, has_ext))
+}
+
+/// read map header, returns entries count and header size
+pub fn read_map_header(data: &[u8]) -> io::Result<(u32, usize)> {
+    match data.first().map(|b| Marker::from_u8(*b)) {
+        Some(Marker::FixMap(n)) => Ok((n as u32, 1)),
+        Some(Marker::Map16) => Ok((read_be(data, 1, 2)? as u32, 3)),
+        Some(Marker::Map32) => Ok((read_be(data, 1, 4)? as u32, 5)),
+        Some(marker) => Err(make_error(format!("msgpack map expected, found {:?}!", marker), false)),
+        None => truncated(),
+    }
+}
+
+/// bytes of string value at the start of data, None if value is not a string
+fn read_str(data: &[u8]) -> io::Result<Option<&[u8]>> {
+    let (len, header_len) = match data.first().map(|b| Marker::from_u8(*b)) {
+        Some(Marker::FixStr(n)) => (n as usize, 1),
+        Some(Marker::Str8) => (read_be(data, 1, 1)?, 2),
+        Some(Marker::Str16) => (read_be(data, 1, 2)?, 3),
+        Some(Marker::Str32) => (read_be(data, 1, 4)?, 5),
+        Some(_) => return Ok(None),
+        None => return truncated(),
+    };
+    match data.get(header_len..header_len + len) {
+        Some(bytes) => Ok(Some(bytes)),
+        None => truncated(),
+    }
+}
+
+/// data starting at zero based element of array, None if value is not an array or it is shorter
+pub fn array_element(data: &[u8], index: u32) -> io::Result<Option<&[u8]>> {
+    let (count, mut pos) = match data.first().map(|b| Marker::from_u8(*b)) {
+        Some(Marker::FixArray(_)) | Some(Marker::Array16) | Some(Marker::Array32) => read_array_header(data)?,
+        _ => return Ok(None),
+    };
+    if index >= count {
+        return Ok(None);
+    }
+    for _ in 0..index {
+        pos += value_len(&data[pos..])?;
+    }
+    Ok(Some(&data[pos..]))
+}
+
+/// data starting at value of string key in map, None if value is not a map or key is absent
+pub fn map_value<'a>(data: &'a [u8], key: &str) -> io::Result<Option<&'a [u8]>> {
+    let (count, mut pos) = match data.first().map(|b| Marker::from_u8(*b)) {
+        Some(Marker::FixMap(_)) | Some(Marker::Map16) | Some(Marker::Map32) => read_map_header(data)?,
+        _ => return Ok(None),
+    };
+    for _ in 0..count {
+        let key_len = value_len(&data[pos..])?;
+        let found = read_str(&data[pos..])? == Some(key.as_bytes());
+        pos += key_len;
+        if found {
+            return Ok(Some(&data[pos..]));
+        }
+        pos += value_len(&data[pos..])?;
+    }
+    Ok(None)
+}
+
+/// ext type and payload of ext value at the start of data with total value size, None if value is not an ext
+pub fn read_ext(data: &[u8]) -> io::Result<Option<(i8, &[u8], usize)>> {
+    let (len, header_len) = match data.first().map(|b| Marker::from_u8(*b)) {
+        Some(Marker::FixExt1) => (1, 2),
+        Some(Marker::FixExt2) => (2, 2),
+        Some(Marker::FixExt4) => (4, 2),
+        Some(Marker::FixExt8) => (8, 2),
+        Some(Marker::FixExt16) => (16, 2),
+        Some(Marker::Ext8) => (read_be(data, 1, 1)?, 3),
+        Some(Marker::Ext16) => (read_be(data, 1, 2)?, 4),
+        Some(Marker::Ext32) => (read_be(data, 1, 4)?, 6),
+        Some(_) => return Ok(None),
+        None => return truncated(),
+    };
+    match data.get(header_len..header_len + len) {
+        Some(payload) => Ok(Some((data[header_len - 1] as i8, payload, header_len + len))),
+        None => truncated(),
+    }
+}
+
+/// write ext value, fixext markers are used when payload size allows
+pub fn write_ext(buf: &mut Vec<u8>, ext_type: i8, payload: &[u8]) {
+    match payload.len() {
+        1 => buf.push(0xd4),
+        2 => buf.push(0xd5),
+        4 => buf.push(0xd6),
+        8 => buf.push(0xd7),
+        16 => buf.push(0xd8),
+        len if len <= 0xff => buf.extend_from_slice(&[0xc7, len as u8]),
+        len if len <= 0xffff => {
+            buf.push(0xc8);
+            buf.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            buf.push(0xc9);
+            buf.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+    buf.push(ext_type as u8);
+    buf.extend_from_slice(payload);
+}
+
+/// bytes of binary value at the start of data with total value size, None if value is not a binary
+pub fn read_bin(data: &[u8]) -> io::Result<Option<(&[u8], usize)>> {
+    let (len, header_len) = match data.first().map(|b| Marker::from_u8(*b)) {
+        Some(Marker::Bin8) => (read_be(data, 1, 1)?, 2),
+        Some(Marker::Bin16) => (read_be(data, 1, 2)?, 3),
+        Some(Marker::Bin32) => (read_be(data, 1, 4)?, 5),
+        Some(_) => return Ok(None),
+        None => return truncated(),
+    };
+    match data.get(header_len..header_len + len) {
+        Some(bytes) => Ok(Some((bytes, header_len + len))),
+        None => truncated(),
+    }
+}
+
+/// size of encoded binary value with len bytes
+pub(crate) fn bin_len(len: usize) -> usize {
+    match len {
+        0..=0xff => 2 + len,
+        0x100..=0xffff => 3 + len,
+        _ => 5 + len,
+    }
+}
+
+/// write binary value
+pub fn write_bin(buf: &mut Vec<u8>, bytes: &[u8]) {
+    match bytes.len() {
+        len if len <= 0xff => buf.extend_from_slice(&[0xc4, len as u8]),
+        len if len <= 0xffff => {
+            buf.push(0xc5);
+            buf.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            buf.push(0xc6);
+            buf.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+    buf.extend_from_slice(bytes);
+}
+
+/// copy the first value of data to out, f may replace any nested value by writing it to out and returning its size,
+/// returns size of the copied value
+pub fn rewrite_value<F>(data: &[u8], out: &mut Vec<u8>, f: &mut F) -> io::Result<usize>
+    where F: FnMut(&[u8], &mut Vec<u8>) -> io::Result<Option<usize>>
+{
+    if let Some(len) = f(data, out)? {
+        return Ok(len);
+    }
+    let (count, header_len) = match data.first().map(|b| Marker::from_u8(*b)) {
+        Some(Marker::FixArray(_)) | Some(Marker::Array16) | Some(Marker::Array32) => read_array_header(data)?,
+        Some(Marker::FixMap(_)) | Some(Marker::Map16) | Some(Marker::Map32) => read_map_header(data).map(|(n, l)| (2 * n, l))?,
+        _ => {
+            let len = value_len(data)?;
+            out.extend_from_slice(&data[..len]);
+            return Ok(len);
+        }
+    };
+    out.extend_from_slice(&data[..header_len]);
+    let mut pos = header_len;
+    for _ in 0..count {
+        pos += rewrite_value(&data[pos..], out, f)?;
+    }
+    Ok(pos)
+}
+
+/// signed or unsigned integer at the start of data with its size, u64 values over i64::MAX are errors
+pub fn read_int(data: &[u8]) -> io::Result<(i64, usize)> {
+    let (value, len) = match data.first().map(|b| Marker::from_u8(*b)) {
+        Some(Marker::FixPos(n)) => (n as i64, 1),
+        Some(Marker::FixNeg(n)) => (n as i64, 1),
+        Some(Marker::U8) => (read_be(data, 1, 1)? as i64, 2),
+        Some(Marker::U16) => (read_be(data, 1, 2)? as i64, 3),
+        Some(Marker::U32) => (read_be(data, 1, 4)? as i64, 5),
+        Some(Marker::U64) => match read_be(data, 1, 8)? as u64 {
+            v if v > i64::MAX as u64 => return Err(make_error(format!("integer {} is out of range!", v), false)),
+            v => (v as i64, 9),
+        },
+        Some(Marker::I8) => (read_be(data, 1, 1)? as i8 as i64, 2),
+        Some(Marker::I16) => (read_be(data, 1, 2)? as i16 as i64, 3),
+        Some(Marker::I32) => (read_be(data, 1, 4)? as i32 as i64, 5),
+        Some(Marker::I64) => (read_be(data, 1, 8)? as i64, 9),
+        Some(marker) => return Err(make_error(format!("msgpack integer expected, found {:?}!", marker), false)),
+        None => return truncated(),
+    };
+    Ok((value, len))
+}
@@ -0,0 +1,133 @@
+use serde::de::DeserializeOwned;
+use std::io;
+use std::mem;
+use std::os::raw::{c_char, c_int, c_uchar};
+use crate::tarantool::api::*;
+use crate::tarantool::internal::*;
+use crate::tarantool::msgpack::*;
+
+/// array indexes in path are 1 based, like tuple['data.b[2]'] in lua
+const PATH_INDEX_BASE: u32 = 1;
+
+type TupleFieldByPathFn = unsafe extern "C" fn(*const c_uchar, *const c_char, u32, c_int) -> *const c_uchar;
+
+lazy_static! {
+    /// box_tuple_field_by_path is exported only by newer servers, resolved once at first use
+    static ref TUPLE_FIELD_BY_PATH: Option<TupleFieldByPathFn> = unsafe {
+        let ptr = libc::dlsym(libc::RTLD_DEFAULT, b"box_tuple_field_by_path\0".as_ptr() as *const c_char);
+        if ptr.is_null() {
+            None
+        } else {
+            Some(mem::transmute::<*mut libc::c_void, TupleFieldByPathFn>(ptr))
+        }
+    };
+}
+
+/// Component of json path
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PathPart<'a> {
+    /// zero based array index
+    Index(u32),
+    /// map key or, at the start of path, field name from space format
+    Key(&'a str),
+}
+
+fn invalid_path<T>(path: &str, pos: usize) -> io::Result<T> {
+    Err(make_error(format!("Invalid json path {:?} at position {}!", path, pos + 1), false))
+}
+
+/// parse path like 'data.b[2].name', '[3]["key"]' or 'name'
+fn parse_path(path: &str) -> io::Result<Vec<PathPart<'_>>> {
+    let bytes = path.as_bytes();
+    let mut parts = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'[' => {
+                let end = match path[pos..].find(']') {
+                    Some(end) => pos + end,
+                    None => return invalid_path(path, pos),
+                };
+                let inner = &path[pos + 1..end];
+                let quoted = inner.len() >= 2 && (inner.starts_with('"') && inner.ends_with('"') || inner.starts_with('\'') && inner.ends_with('\''));
+                if quoted {
+                    parts.push(PathPart::Key(&inner[1..inner.len() - 1]));
+                } else {
+                    match inner.parse::<u32>() {
+                        Ok(index) if index >= PATH_INDEX_BASE => parts.push(PathPart::Index(index - PATH_INDEX_BASE)),
+                        _ => return invalid_path(path, pos + 1),
+                    }
+                }
+                pos = end + 1;
+            }
+            _ => {
+                if bytes[pos] == b'.' && !parts.is_empty() {
+                    pos += 1;
+                } else if !parts.is_empty() {
+                    return invalid_path(path, pos);
+                }
+                let end = path[pos..].find(['.', '[', ']']).map(|end| pos + end).unwrap_or(bytes.len());
+                if end == pos {
+                    return invalid_path(path, pos);
+                }
+                parts.push(PathPart::Key(&path[pos..end]));
+                pos = end;
+            }
+        }
+    }
+    if parts.is_empty() {
+        return invalid_path(path, 0);
+    }
+    Ok(parts)
+}
+
+impl<'ctx> TarantoolTuple<'ctx> {
+    /// raw msgpack of value addressed by json path, None if there is no such value
+    ///
+    /// path starts with field name from space format or field number in brackets, array indexes are 1 based
+    pub fn get_path_data(&self, path: &str) -> io::Result<Option<&[u8]>> {
+        let parts = parse_path(path)?;
+        if let Some(field_by_path) = *TUPLE_FIELD_BY_PATH {
+            let field = unsafe { field_by_path(self.row_data, path.as_ptr() as *const c_char, path.len() as u32, PATH_INDEX_BASE as c_int) };
+            if field.is_null() {
+                return Ok(None);
+            }
+            //value is measured in place, tuple data is not copied
+            return self.get_data_at(field).map(Some);
+        }
+
+        let field_no = match parts[0] {
+            PathPart::Index(field_no) => field_no,
+            PathPart::Key(name) => self.field_no(name)?,
+        };
+        let mut value = match self.get_field_data(field_no)? {
+            Some(field) => field,
+            None => return Ok(None),
+        };
+        for part in parts[1..].iter() {
+            let next = match part {
+                PathPart::Index(index) => array_element(value, *index)?,
+                PathPart::Key(key) => map_value(value, key)?,
+            };
+            value = match next {
+                Some(next) => next,
+                None => return Ok(None),
+            };
+        }
+        let len = value_len(value)?;
+        Ok(Some(&value[..len]))
+    }
+
+    /// decode only value addressed by json path, absent value is decoded as nil
+    ///
+    /// # Examples
+    ///
+    /// let name: Option<String> = row.get_path("data.b[2].name")?;
+    ///
+    pub fn get_path<V>(&self, path: &str) -> io::Result<V> where V: DeserializeOwned {
+        match self.get_path_data(path)? {
+            Some(value) => decode_serde(value),
+            None => decode_serde(&[MSGPACK_NIL][..]),
+        }
+    }
+}
@@ -0,0 +1,419 @@
+use regex::bytes::Regex;
+use rmp::Marker;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::convert::TryInto;
+use std::io;
+use std::ops::Not;
+use crate::tarantool::api::*;
+use crate::tarantool::internal::*;
+use crate::tarantool::msgpack::*;
+
+/// Field of row checked by predicate, zero based number or name in space format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldRef {
+    No(u32),
+    Name(String),
+}
+
+impl From<u32> for FieldRef {
+    fn from(no: u32) -> FieldRef {
+        FieldRef::No(no)
+    }
+}
+
+impl<'a> From<&'a str> for FieldRef {
+    fn from(name: &'a str) -> FieldRef {
+        FieldRef::Name(name.to_string())
+    }
+}
+
+impl From<String> for FieldRef {
+    fn from(name: String) -> FieldRef {
+        FieldRef::Name(name)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    /// values are encoded once when predicate is made
+    Cmp(FieldRef, CmpOp, Vec<u8>),
+    Prefix(FieldRef, Vec<u8>),
+    Contains(FieldRef, Vec<u8>),
+    Regex(FieldRef, Regex),
+    In(FieldRef, Vec<Vec<u8>>),
+    IsNull(FieldRef),
+    And(Vec<Node>),
+    Or(Vec<Node>),
+    Not(Box<Node>),
+    /// error of making predicate, returned on first check
+    Invalid(String),
+}
+
+/// Filter checked on raw msgpack of rows in place, fields are not decoded and rows are not copied
+///
+/// numbers are compared by value (integers with floats too), strings and binaries bytewise,
+/// values of different types are not equal and not ordered, absent field is nil
+///
+/// # Examples
+///
+/// let filter = Predicate::contains("name", "land")
+///     .and(Predicate::in_set(2, &["Europe", "Asia"]))
+///     .and(!Predicate::is_null("sub_region"));
+/// for row in tarantool.index_iterator_all(COUNTRY_SPACE, COUNTRY_INDEX)?.filter_by(filter).typed::<CountryData>() {
+///     result.push(row?);
+/// }
+///
+#[derive(Debug, Clone)]
+pub struct Predicate {
+    node: Node,
+}
+
+fn encode_value<V: Serialize>(value: &V) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    serialize_to_vec(&mut data, value).map_err(|e| e.to_string())?;
+    Ok(data)
+}
+
+impl Predicate {
+    fn cmp<F: Into<FieldRef>, V: Serialize>(field: F, op: CmpOp, value: &V) -> Predicate {
+        let node = match encode_value(value) {
+            Ok(data) => Node::Cmp(field.into(), op, data),
+            Err(e) => Node::Invalid(e),
+        };
+        Predicate { node }
+    }
+
+    pub fn eq<F: Into<FieldRef>, V: Serialize>(field: F, value: &V) -> Predicate {
+        Predicate::cmp(field, CmpOp::Eq, value)
+    }
+
+    pub fn ne<F: Into<FieldRef>, V: Serialize>(field: F, value: &V) -> Predicate {
+        Predicate::cmp(field, CmpOp::Ne, value)
+    }
+
+    pub fn lt<F: Into<FieldRef>, V: Serialize>(field: F, value: &V) -> Predicate {
+        Predicate::cmp(field, CmpOp::Lt, value)
+    }
+
+    pub fn le<F: Into<FieldRef>, V: Serialize>(field: F, value: &V) -> Predicate {
+        Predicate::cmp(field, CmpOp::Le, value)
+    }
+
+    pub fn gt<F: Into<FieldRef>, V: Serialize>(field: F, value: &V) -> Predicate {
+        Predicate::cmp(field, CmpOp::Gt, value)
+    }
+
+    pub fn ge<F: Into<FieldRef>, V: Serialize>(field: F, value: &V) -> Predicate {
+        Predicate::cmp(field, CmpOp::Ge, value)
+    }
+
+    /// string field starts with prefix
+    pub fn prefix<F: Into<FieldRef>>(field: F, prefix: &str) -> Predicate {
+        Predicate { node: Node::Prefix(field.into(), prefix.as_bytes().to_vec()) }
+    }
+
+    /// string field contains substring
+    pub fn contains<F: Into<FieldRef>>(field: F, substring: &str) -> Predicate {
+        Predicate { node: Node::Contains(field.into(), substring.as_bytes().to_vec()) }
+    }
+
+    /// string field matches regex, use (?i) for case insensitive match
+    pub fn regex<F: Into<FieldRef>>(field: F, pattern: &str) -> Predicate {
+        let node = match Regex::new(pattern) {
+            Ok(regex) => Node::Regex(field.into(), regex),
+            Err(e) => Node::Invalid(format!("Invalid regex {:?}! {}", pattern, e)),
+        };
+        Predicate { node }
+    }
+
+    /// field is equal to one of values
+    pub fn in_set<F: Into<FieldRef>, V: Serialize>(field: F, values: &[V]) -> Predicate {
+        let node = match values.iter().map(encode_value).collect() {
+            Ok(values) => Node::In(field.into(), values),
+            Err(e) => Node::Invalid(e),
+        };
+        Predicate { node }
+    }
+
+    /// field is nil or absent
+    pub fn is_null<F: Into<FieldRef>>(field: F) -> Predicate {
+        Predicate { node: Node::IsNull(field.into()) }
+    }
+
+    /// true when all predicates are true, also for empty list
+    pub fn all(predicates: Vec<Predicate>) -> Predicate {
+        Predicate { node: Node::And(predicates.into_iter().map(|p| p.node).collect()) }
+    }
+
+    /// true when any of predicates is true, false for empty list
+    pub fn any(predicates: Vec<Predicate>) -> Predicate {
+        Predicate { node: Node::Or(predicates.into_iter().map(|p| p.node).collect()) }
+    }
+
+    pub fn and(self, other: Predicate) -> Predicate {
+        match self.node {
+            Node::And(mut nodes) => {
+                nodes.push(other.node);
+                Predicate { node: Node::And(nodes) }
+            }
+            node => Predicate { node: Node::And(vec![node, other.node]) },
+        }
+    }
+
+    pub fn or(self, other: Predicate) -> Predicate {
+        match self.node {
+            Node::Or(mut nodes) => {
+                nodes.push(other.node);
+                Predicate { node: Node::Or(nodes) }
+            }
+            node => Predicate { node: Node::Or(vec![node, other.node]) },
+        }
+    }
+
+    /// copy of predicate with field names changed to numbers from format of the space
+    pub fn bind(&self, ctx: &TarantoolContext, space_id: u32) -> io::Result<Predicate> {
+        Ok(Predicate { node: bind_node(&self.node, ctx, space_id)? })
+    }
+
+    /// check row, fields given by name are looked up in format of row space
+    pub fn matches(&self, row: &TarantoolTuple) -> io::Result<bool> {
+        eval(&self.node, row)
+    }
+}
+
+impl Not for Predicate {
+    type Output = Predicate;
+
+    fn not(self) -> Predicate {
+        match self.node {
+            Node::Not(node) => Predicate { node: *node },
+            node => Predicate { node: Node::Not(Box::new(node)) },
+        }
+    }
+}
+
+fn bind_field(field: &FieldRef, ctx: &TarantoolContext, space_id: u32) -> io::Result<FieldRef> {
+    match field {
+        FieldRef::No(no) => Ok(FieldRef::No(*no)),
+        FieldRef::Name(name) => Ok(FieldRef::No(ctx.field_no(space_id, name)?)),
+    }
+}
+
+fn bind_node(node: &Node, ctx: &TarantoolContext, space_id: u32) -> io::Result<Node> {
+    let bind_all = |nodes: &[Node]| nodes.iter().map(|node| bind_node(node, ctx, space_id)).collect::<io::Result<Vec<_>>>();
+    Ok(match node {
+        Node::Cmp(field, op, value) => Node::Cmp(bind_field(field, ctx, space_id)?, *op, value.clone()),
+        Node::Prefix(field, prefix) => Node::Prefix(bind_field(field, ctx, space_id)?, prefix.clone()),
+        Node::Contains(field, substring) => Node::Contains(bind_field(field, ctx, space_id)?, substring.clone()),
+        Node::Regex(field, regex) => Node::Regex(bind_field(field, ctx, space_id)?, regex.clone()),
+        Node::In(field, values) => Node::In(bind_field(field, ctx, space_id)?, values.clone()),
+        Node::IsNull(field) => Node::IsNull(bind_field(field, ctx, space_id)?),
+        Node::And(nodes) => Node::And(bind_all(nodes)?),
+        Node::Or(nodes) => Node::Or(bind_all(nodes)?),
+        Node::Not(node) => Node::Not(Box::new(bind_node(node, ctx, space_id)?)),
+        Node::Invalid(e) => Node::Invalid(e.clone()),
+    })
+}
+
+/// scalar msgpack value read in place
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum Scalar<'a> {
+    Nil,
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    Str(&'a [u8]),
+    Bin(&'a [u8]),
+    /// arrays, maps and ext values, compared only for equality by encoded bytes
+    Other,
+}
+
+pub(crate) fn read_scalar(data: &[u8]) -> io::Result<Scalar<'_>> {
+    let marker = match data.first() {
+        Some(b) => Marker::from_u8(*b),
+        None => return Ok(Scalar::Nil),
+    };
+    let bytes = |pos: usize, size: usize| -> io::Result<&[u8]> {
+        data.get(pos..pos + size).ok_or_else(|| make_error("truncated msgpack data!".to_string(), false))
+    };
+    let len = |size: usize| -> io::Result<usize> {
+        Ok(bytes(1, size)?.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize))
+    };
+    Ok(match marker {
+        Marker::Null => Scalar::Nil,
+        Marker::True => Scalar::Bool(true),
+        Marker::False => Scalar::Bool(false),
+        Marker::U64 => Scalar::Int(u64::from_be_bytes(bytes(1, 8)?.try_into().unwrap_or_default()) as i128),
+        Marker::FixPos(_) | Marker::FixNeg(_) | Marker::U8 | Marker::U16 | Marker::U32
+        | Marker::I8 | Marker::I16 | Marker::I32 | Marker::I64 => Scalar::Int(read_int(data)?.0 as i128),
+        Marker::F32 => Scalar::Float(f32::from_be_bytes(bytes(1, 4)?.try_into().unwrap_or_default()) as f64),
+        Marker::F64 => Scalar::Float(f64::from_be_bytes(bytes(1, 8)?.try_into().unwrap_or_default())),
+        Marker::FixStr(n) => Scalar::Str(bytes(1, n as usize)?),
+        Marker::Str8 => Scalar::Str(bytes(2, len(1)?)?),
+        Marker::Str16 => Scalar::Str(bytes(3, len(2)?)?),
+        Marker::Str32 => Scalar::Str(bytes(5, len(4)?)?),
+        Marker::Bin8 => Scalar::Bin(bytes(2, len(1)?)?),
+        Marker::Bin16 => Scalar::Bin(bytes(3, len(2)?)?),
+        Marker::Bin32 => Scalar::Bin(bytes(5, len(4)?)?),
+        _ => Scalar::Other,
+    })
+}
+
+pub(crate) fn compare_scalars(a: Scalar, b: Scalar) -> Option<Ordering> {
+    match (a, b) {
+        (Scalar::Nil, Scalar::Nil) => Some(Ordering::Equal),
+        (Scalar::Bool(a), Scalar::Bool(b)) => Some(a.cmp(&b)),
+        (Scalar::Int(a), Scalar::Int(b)) => Some(a.cmp(&b)),
+        (Scalar::Int(a), Scalar::Float(b)) => (a as f64).partial_cmp(&b),
+        (Scalar::Float(a), Scalar::Int(b)) => a.partial_cmp(&(b as f64)),
+        (Scalar::Float(a), Scalar::Float(b)) => a.partial_cmp(&b),
+        (Scalar::Str(a), Scalar::Str(b)) | (Scalar::Bin(a), Scalar::Bin(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+fn values_equal(field: &[u8], value: &[u8]) -> io::Result<bool> {
+    let (a, b) = (read_scalar(field)?, read_scalar(value)?);
+    if let (Scalar::Other, Scalar::Other) = (a, b) {
+        return Ok(field == value);
+    }
+    Ok(compare_scalars(a, b) == Some(Ordering::Equal))
+}
+
+const NIL_FIELD: [u8; 1] = [MSGPACK_NIL];
+
+fn field_data<'a>(row: &'a TarantoolTuple, field: &FieldRef) -> io::Result<&'a [u8]> {
+    let no = match field {
+        FieldRef::No(no) => *no,
+        FieldRef::Name(name) => row.field_no(name)?,
+    };
+    //last field is bounded by the end of tuple data, row is not copied for it either
+    Ok(row.get_field_data(no)?.unwrap_or(&NIL_FIELD))
+}
+
+fn field_str<'a>(row: &'a TarantoolTuple, field: &FieldRef) -> io::Result<Option<&'a [u8]>> {
+    match read_scalar(field_data(row, field)?)? {
+        Scalar::Str(bytes) => Ok(Some(bytes)),
+        _ => Ok(None),
+    }
+}
+
+fn eval(node: &Node, row: &TarantoolTuple) -> io::Result<bool> {
+    match node {
+        Node::Cmp(field, op, value) => {
+            let data = field_data(row, field)?;
+            match op {
+                CmpOp::Eq => values_equal(data, value),
+                CmpOp::Ne => Ok(!values_equal(data, value)?),
+                op => {
+                    let ord = compare_scalars(read_scalar(data)?, read_scalar(value)?);
+                    Ok(match (op, ord) {
+                        (_, None) => false,
+                        (CmpOp::Lt, Some(ord)) => ord == Ordering::Less,
+                        (CmpOp::Le, Some(ord)) => ord != Ordering::Greater,
+                        (CmpOp::Gt, Some(ord)) => ord == Ordering::Greater,
+                        (_, Some(ord)) => ord != Ordering::Less,
+                    })
+                }
+            }
+        }
+        Node::Prefix(field, prefix) => Ok(field_str(row, field)?.is_some_and(|s| s.starts_with(prefix))),
+        Node::Contains(field, substring) => Ok(field_str(row, field)?
+            .is_some_and(|s| substring.is_empty() || s.windows(substring.len()).any(|w| w == &substring[..]))),
+        Node::Regex(field, regex) => Ok(field_str(row, field)?.is_some_and(|s| regex.is_match(s))),
+        Node::In(field, values) => {
+            let data = field_data(row, field)?;
+            for value in values {
+                if values_equal(data, value)? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+        Node::IsNull(field) => Ok(read_scalar(field_data(row, field)?)? == Scalar::Nil),
+        Node::And(nodes) => {
+            for node in nodes {
+                if !eval(node, row)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        Node::Or(nodes) => {
+            for node in nodes {
+                if eval(node, row)? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+        Node::Not(node) => Ok(!eval(node, row)?),
+        Node::Invalid(e) => Err(make_error(format!("Invalid predicate! {}", e), false)),
+    }
+}
+
+impl<'ctx> TarantoolTuple<'ctx> {
+    pub fn matches(&self, predicate: &Predicate) -> io::Result<bool> {
+        predicate.matches(self)
+    }
+}
+
+/// Rows matching predicate, names of fields are changed to numbers once for space of rows
+pub struct PredicateIterator<I> {
+    inner: I,
+    predicate: Predicate,
+    bound: Option<(u32, Predicate)>,
+}
+
+impl<I> PredicateIterator<I> {
+    pub(crate) fn new(inner: I, predicate: Predicate) -> PredicateIterator<I> {
+        PredicateIterator { inner, predicate, bound: None }
+    }
+}
+
+impl<'ctx, I> PredicateIterator<I> where I: Iterator<Item=io::Result<TarantoolTuple<'ctx>>> {
+    fn check(&mut self, row: &TarantoolTuple<'ctx>) -> io::Result<bool> {
+        let space_id = match row.space_id() {
+            Some(space_id) => space_id,
+            None => return self.predicate.matches(row),
+        };
+        match self.bound {
+            Some((bound_space_id, ref predicate)) if bound_space_id == space_id => predicate.matches(row),
+            _ => {
+                let predicate = self.predicate.bind(&TarantoolContext::new_ffi(), space_id)?;
+                let res = predicate.matches(row);
+                self.bound = Some((space_id, predicate));
+                res
+            }
+        }
+    }
+}
+
+impl<'ctx, I> Iterator for PredicateIterator<I> where I: Iterator<Item=io::Result<TarantoolTuple<'ctx>>> {
+    type Item = io::Result<TarantoolTuple<'ctx>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let row = match self.inner.next()? {
+                Ok(row) => row,
+                Err(e) => return Some(Err(e)),
+            };
+            match self.check(&row) {
+                Ok(true) => return Some(Ok(row)),
+                Ok(false) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
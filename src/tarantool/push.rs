@@ -0,0 +1,145 @@
+use serde::Serialize;
+use std::io;
+use std::mem;
+use std::os::raw::{c_char, c_int};
+use crate::tarantool::api::*;
+use crate::tarantool::internal::*;
+use crate::tarantool::msgpack::*;
+
+type SessionPushFn = unsafe extern "C" fn(*const u8, *const u8) -> c_int;
+
+lazy_static! {
+    /// box_session_push is exported only by newer servers, lua box.session.push is called otherwise
+    static ref SESSION_PUSH: Option<SessionPushFn> = unsafe {
+        let ptr = libc::dlsym(libc::RTLD_DEFAULT, b"box_session_push\0".as_ptr() as *const c_char);
+        if ptr.is_null() {
+            None
+        } else {
+            Some(mem::transmute::<*mut libc::c_void, SessionPushFn>(ptr))
+        }
+    };
+}
+
+/// decodes pushed msgpack, decode returns position as second value so it is cut off before push,
+/// older servers return nil and error instead of raising it
+const SESSION_PUSH_LUA: &str = "local ok, err = box.session.push((require('msgpack').decode((...)))) \
+                                if err ~= nil then error(err) end \
+                                return ok";
+
+/// rows encoded one by one and pushed as msgpack array when chunk is full
+struct PushChunk {
+    rows: Vec<u8>,
+    count: u32,
+    size: u32,
+    pushed: usize,
+    buf: Vec<u8>,
+}
+
+impl PushChunk {
+    fn new(size: usize) -> io::Result<PushChunk> {
+        if size == 0 {
+            return Err(make_error("Chunk size of pushed rows must be positive!".to_string(), false));
+        }
+        Ok(PushChunk { rows: Vec::new(), count: 0, size: size as u32, pushed: 0, buf: Vec::new() })
+    }
+
+    fn added(&mut self, ctx: &TarantoolContext) -> io::Result<()> {
+        self.count += 1;
+        if self.count == self.size {
+            self.flush(ctx)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, ctx: &TarantoolContext) -> io::Result<()> {
+        if self.count == 0 {
+            return Ok(());
+        }
+        self.buf.clear();
+        write_array_header(&mut self.buf, self.count);
+        self.buf.extend_from_slice(&self.rows);
+        ctx.session_push_data(&self.buf)?;
+        self.pushed += self.count as usize;
+        self.rows.clear();
+        self.count = 0;
+        Ok(())
+    }
+}
+
+impl TarantoolContext {
+    /// send value to client before proc returns, like box.session.push; net.box client gets it in on_push
+    /// or by iterating future, local calls ignore pushes
+    ///
+    /// # Examples
+    ///
+    /// tarantool.session_push(&("progress", done, total))?;
+    ///
+    pub fn session_push<SER: Serialize>(&self, value: &SER) -> io::Result<()> {
+        let mut data = Vec::new();
+        serialize_to_vec(&mut data, value)?;
+        self.session_push_data(&data)
+    }
+
+    /// push already encoded msgpack value, data must hold exactly one value
+    pub fn session_push_data(&self, data: &[u8]) -> io::Result<()> {
+        let len = value_len(data)?;
+        if len != data.len() {
+            return Err(make_error(format!("Pushed data must be one msgpack value, {} bytes left!", data.len() - len), false));
+        }
+        match *SESSION_PUSH {
+            Some(session_push) => {
+                let data = data.as_ptr_range();
+                if unsafe { session_push(data.start, data.end) } != 0 {
+                    return make_error_result("error on session push!".to_string());
+                }
+                Ok(())
+            }
+            None => self.session_push_data_lua(data),
+        }
+    }
+
+    /// push already encoded msgpack value with lua box.session.push, used when server doesn't export box_session_push
+    pub fn session_push_data_lua(&self, data: &[u8]) -> io::Result<()> {
+        let mut call = self.init_eval(SESSION_PUSH_LUA)?;
+        call.push_str(data);
+        call.call()?;
+        call.pop_boolean()?;
+        Ok(())
+    }
+
+    /// push rows in arrays of up to chunk_size rows, returns number of pushed rows;
+    /// rows already pushed stay pushed when iterator fails
+    ///
+    /// # Examples
+    ///
+    /// let rows = tarantool.index_iterator_all(COUNTRY_SPACE, COUNTRY_INDEX)?
+    ///     .with_yield_policy(YieldPolicy::EveryRows(1000))?
+    ///     .typed::<CountryData>();
+    /// let count = tarantool.session_push_stream(rows, 100)?;
+    ///
+    pub fn session_push_stream<I, SER>(&self, rows: I, chunk_size: usize) -> io::Result<usize>
+        where I: IntoIterator<Item=io::Result<SER>>,
+              SER: Serialize
+    {
+        let mut chunk = PushChunk::new(chunk_size)?;
+        for row in rows {
+            serialize_to_vec(&mut chunk.rows, &row?)?;
+            chunk.added(self)?;
+        }
+        chunk.flush(self)?;
+        Ok(chunk.pushed)
+    }
+
+    /// same as session_push_stream for tuples, they are copied without decoding
+    pub fn session_push_tuples<'t, I>(&self, rows: I, chunk_size: usize) -> io::Result<usize>
+        where I: IntoIterator<Item=io::Result<TarantoolTuple<'t>>>
+    {
+        let mut chunk = PushChunk::new(chunk_size)?;
+        for row in rows {
+            chunk.rows.extend_from_slice(row?.get_data());
+            chunk.added(self)?;
+        }
+        chunk.flush(self)?;
+        Ok(chunk.pushed)
+    }
+}
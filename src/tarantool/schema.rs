@@ -0,0 +1,321 @@
+use rmpv::Value;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use std::error;
+use std::fmt;
+use std::io;
+use crate::tarantool::api::*;
+use crate::tarantool::dict::*;
+use crate::tarantool::internal::*;
+
+pub(crate) const SPACE_SPACE_ID: u32 = 280;
+pub(crate) const INDEX_SPACE_ID: u32 = 288;
+const COLLATION_SPACE_ID: u32 = 276;
+const PRIMARY_INDEX_ID: u32 = 0;
+
+/// Field of space format (space:format() in lua)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldInfo {
+    pub name: String,
+    /// type name as in format, 'unsigned', 'string' etc.
+    pub type_name: String,
+    pub is_nullable: bool,
+}
+
+impl FieldInfo {
+    pub fn new(name: &str, type_name: &str) -> FieldInfo {
+        FieldInfo { name: name.to_string(), type_name: type_name.to_string(), is_nullable: false }
+    }
+
+    pub fn field_type(&self) -> Option<FieldType> {
+        FieldType::from_name(&self.type_name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SpaceInfo {
+    pub id: u32,
+    pub name: String,
+    pub engine: String,
+    /// 0 if field count is not fixed
+    pub field_count: u32,
+    pub format: Vec<FieldInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub enum IndexType {
+    Tree,
+    Hash,
+    Rtree,
+    Bitset,
+    Other(String),
+}
+
+impl IndexType {
+    pub fn from_name(name: &str) -> IndexType {
+        match name.to_lowercase().as_str() {
+            "tree" => IndexType::Tree,
+            "hash" => IndexType::Hash,
+            "rtree" => IndexType::Rtree,
+            "bitset" => IndexType::Bitset,
+            _ => IndexType::Other(name.to_string()),
+        }
+    }
+
+    /// name as in box.space.*:create_index
+    pub fn name(&self) -> &str {
+        match self {
+            IndexType::Tree => "TREE",
+            IndexType::Hash => "HASH",
+            IndexType::Rtree => "RTREE",
+            IndexType::Bitset => "BITSET",
+            IndexType::Other(name) => name,
+        }
+    }
+}
+
+/// Part of index key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IndexPart {
+    /// zero based field number
+    pub field: u32,
+    pub type_name: String,
+    pub collation: Option<String>,
+    pub is_nullable: bool,
+    /// json path inside field for multikey and json indexes
+    pub path: Option<String>,
+}
+
+impl IndexPart {
+    /// part by zero based field number
+    pub fn new(field: u32, type_name: &str) -> IndexPart {
+        IndexPart { field, type_name: type_name.to_string(), collation: None, is_nullable: false, path: None }
+    }
+
+    pub fn field_type(&self) -> Option<FieldType> {
+        FieldType::from_name(&self.type_name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IndexInfo {
+    pub space_id: u32,
+    pub id: u32,
+    pub name: String,
+    pub index_type: IndexType,
+    pub unique: bool,
+    pub parts: Vec<IndexPart>,
+}
+
+impl IndexInfo {
+    /// zero based numbers of fields which make up key, in key order
+    pub fn key_fields(&self) -> Vec<u32> {
+        self.parts.iter().map(|part| part.field).collect()
+    }
+}
+
+fn map_get<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    value.as_map()
+        .and_then(|entries| entries.iter().find(|(k, _)| k.as_str() == Some(key)))
+        .map(|(_, v)| v)
+}
+
+fn parse_error<T>(what: &str, id: u32) -> io::Result<T> {
+    Err(make_error(format!("Can't parse {} definition! id={}", what, id), false))
+}
+
+impl SpaceInfo {
+    /// parse _space tuple: [id, owner, name, engine, field_count, flags, format]
+    pub fn from_tuple(row: &TarantoolTuple) -> io::Result<SpaceInfo> {
+        let (id, _owner, name, engine, field_count, _flags, format): (u32, u32, String, String, u32, Value, Vec<Value>) = row.decode()?;
+        let format = format.iter()
+            .map(|field| {
+                let name = map_get(field, "name").and_then(|v| v.as_str());
+                let type_name = map_get(field, "type").and_then(|v| v.as_str()).unwrap_or("any");
+                let is_nullable = map_get(field, "is_nullable").and_then(|v| v.as_bool()).unwrap_or(false);
+                match name {
+                    Some(name) => Ok(FieldInfo { name: name.to_string(), type_name: type_name.to_string(), is_nullable }),
+                    None => parse_error("space format", id),
+                }
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(SpaceInfo { id, name, engine, field_count, format })
+    }
+
+    /// zero based number of field by its name in space format
+    pub fn field_no(&self, name: &str) -> Option<u32> {
+        self.format.iter().position(|field| field.name == name).map(|v| v as u32)
+    }
+
+    /// check that fields of struct T go in the same order as fields of space format,
+    /// trailing format fields which are absent in struct are allowed
+    pub fn check_struct<'de, T>(&self) -> io::Result<()> where T: Deserialize<'de> {
+        let names = match struct_field_names::<T>() {
+            Some(names) => names,
+            None => return make_error_result(format!("Type is not a struct! space name={}", self.name)),
+        };
+        if names.len() > self.format.len() {
+            return make_error_result(format!("Struct has {} fields but space format has only {}! space name={}", names.len(), self.format.len(), self.name));
+        }
+        for (i, (name, field)) in names.iter().zip(self.format.iter()).enumerate() {
+            if *name != field.name {
+                return make_error_result(format!("Struct field {} is named {} but space field is {}! space name={}", i, name, field.name, self.name));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl IndexInfo {
+    /// parse _index tuple: [space_id, id, name, type, opts, parts]
+    pub fn from_tuple(row: &TarantoolTuple, ctx: &TarantoolContext) -> io::Result<IndexInfo> {
+        let (space_id, id, name, type_name, opts, parts): (u32, u32, String, String, Value, Vec<Value>) = row.decode()?;
+        let unique = map_get(&opts, "unique").and_then(|v| v.as_bool()).unwrap_or(false);
+        let parts = parts.iter()
+            .map(|part| match part {
+                Value::Map(_) => {
+                    let field = match map_get(part, "field").and_then(|v| v.as_u64()) {
+                        Some(field) => field as u32,
+                        None => return parse_error("index parts", id),
+                    };
+                    let collation = match map_get(part, "collation") {
+                        Some(Value::String(name)) => name.as_str().map(|v| v.to_string()),
+                        Some(collation_id) => match collation_id.as_u64() {
+                            Some(collation_id) => ctx.collation_name(collation_id as u32)?,
+                            None => None,
+                        },
+                        None => None,
+                    };
+                    Ok(IndexPart {
+                        field,
+                        type_name: map_get(part, "type").and_then(|v| v.as_str()).unwrap_or("any").to_string(),
+                        collation,
+                        is_nullable: map_get(part, "is_nullable").and_then(|v| v.as_bool()).unwrap_or(false),
+                        path: map_get(part, "path").and_then(|v| v.as_str()).map(|v| v.to_string()),
+                    })
+                }
+                //old style parts [field, type]
+                Value::Array(items) => match (items.first().and_then(|v| v.as_u64()), items.get(1).and_then(|v| v.as_str())) {
+                    (Some(field), Some(type_name)) => Ok(IndexPart {
+                        field: field as u32,
+                        type_name: type_name.to_string(),
+                        collation: None,
+                        is_nullable: false,
+                        path: None,
+                    }),
+                    _ => parse_error("index parts", id),
+                },
+                _ => parse_error("index parts", id),
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(IndexInfo { space_id, id, name, index_type: IndexType::from_name(&type_name), unique, parts })
+    }
+}
+
+impl TarantoolContext {
+    pub fn space_info_by_id(&self, space_id: u32) -> io::Result<SpaceInfo> {
+        if let Some(info) = search_space_info(space_id) {
+            return Ok(info);
+        }
+        match self.index_get_int(SPACE_SPACE_ID, PRIMARY_INDEX_ID, &(space_id, ))? {
+            Some(row) => SpaceInfo::from_tuple(&row),
+            None => make_error_result(format!("Unknown space id {}!", space_id)),
+        }
+    }
+
+    pub fn space_info<S>(&self, space_name: S) -> io::Result<SpaceInfo>
+        where S: AsRef<[u8]>
+    {
+        let space_id = self.get_space_id(space_name)?;
+        self.space_info_by_id(space_id)
+    }
+
+    /// all indexes of space ordered by index id
+    pub fn space_indexes_by_id(&self, space_id: u32) -> io::Result<Vec<IndexInfo>> {
+        if let Some(indexes) = search_space_indexes(space_id) {
+            return Ok(indexes);
+        }
+        let mut indexes = Vec::new();
+        for row in self.index_iterator_raw("_index", "primary", INDEX_SPACE_ID, PRIMARY_INDEX_ID, IteratorType::EQ, &(space_id, ))? {
+            indexes.push(IndexInfo::from_tuple(&row?, self)?);
+        }
+        Ok(indexes)
+    }
+
+    pub fn space_indexes<S>(&self, space_name: S) -> io::Result<Vec<IndexInfo>>
+        where S: AsRef<[u8]>
+    {
+        let space_id = self.get_space_id(space_name)?;
+        self.space_indexes_by_id(space_id)
+    }
+
+    pub fn index_info_by_id(&self, space_id: u32, index_id: u32) -> io::Result<IndexInfo> {
+        if let Some(info) = search_space_indexes(space_id).and_then(|indexes| indexes.into_iter().find(|v| v.id == index_id)) {
+            return Ok(info);
+        }
+        match self.index_get_int(INDEX_SPACE_ID, PRIMARY_INDEX_ID, &(space_id, index_id))? {
+            Some(row) => IndexInfo::from_tuple(&row, self),
+            None => make_error_result(format!("Unknown index! space id={} index id={}", space_id, index_id)),
+        }
+    }
+
+    pub fn index_info<S, S1>(&self, space_name: S, index_name: S1) -> io::Result<IndexInfo>
+        where S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        let (space_id, index_id) = self.get_space_and_index_id(space_name, index_name)?;
+        self.index_info_by_id(space_id, index_id)
+    }
+
+    fn collation_name(&self, collation_id: u32) -> io::Result<Option<String>> {
+        let row = self.index_get_int(COLLATION_SPACE_ID, PRIMARY_INDEX_ID, &(collation_id, ))?;
+        row.decode_field(1)
+    }
+}
+
+#[derive(Debug)]
+struct FieldNamesCaptured(Option<&'static [&'static str]>);
+
+impl fmt::Display for FieldNamesCaptured {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "struct field names captured")
+    }
+}
+
+impl error::Error for FieldNamesCaptured {}
+
+impl de::Error for FieldNamesCaptured {
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        FieldNamesCaptured(None)
+    }
+}
+
+/// deserializer which only records field names passed by derived Deserialize impl
+struct FieldNamesDeserializer;
+
+impl<'de> Deserializer<'de> for FieldNamesDeserializer {
+    type Error = FieldNamesCaptured;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        Err(FieldNamesCaptured(None))
+    }
+
+    fn deserialize_struct<V>(self, _name: &'static str, fields: &'static [&'static str], _visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        Err(FieldNamesCaptured(Some(fields)))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// names of struct fields in declaration order, None if T is not a struct with derived Deserialize
+pub fn struct_field_names<'de, T>() -> Option<&'static [&'static str]> where T: Deserialize<'de> {
+    match T::deserialize(FieldNamesDeserializer) {
+        Err(FieldNamesCaptured(names)) => names,
+        Ok(_) => None,
+    }
+}
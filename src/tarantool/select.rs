@@ -0,0 +1,248 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::io;
+use crate::tarantool::api::*;
+use crate::tarantool::internal::*;
+use crate::tarantool::msgpack::*;
+
+const EMPTY_KEY: [u8; 1] = [0x90];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum BoundKind {
+    Included,
+    Excluded,
+}
+
+#[derive(Debug)]
+struct Bound {
+    kind: BoundKind,
+    key_data: Vec<u8>,
+}
+
+/// Builder of range queries over index
+///
+/// # Examples
+///
+/// let rows: Vec<RowTypeStruct> = tarantool.select(TEST_SPACE, PRIMARY_INDEX)
+///     .ge(&(10, ))
+///     .lt(&(20, ))
+///     .offset(2)
+///     .limit(5)
+///     .to_vec()?;
+///
+/// bounds are compared with index key by server in order of the index, collations included (prefix of key is allowed)
+#[derive(Debug)]
+pub struct Select<'ctx> {
+    ctx: &'ctx TarantoolContext,
+    space_name: String,
+    index_name: String,
+    lower: Option<Bound>,
+    upper: Option<Bound>,
+    prefix: Option<Vec<u8>>,
+    reverse: bool,
+    limit: Option<usize>,
+    offset: usize,
+    error: Option<io::Error>,
+}
+
+impl<'ctx> Select<'ctx> {
+    fn new(ctx: &'ctx TarantoolContext, space_name: String, index_name: String) -> Select<'ctx> {
+        Select {
+            ctx,
+            space_name,
+            index_name,
+            lower: None,
+            upper: None,
+            prefix: None,
+            reverse: false,
+            limit: None,
+            offset: 0,
+            error: None,
+        }
+    }
+
+    fn make_bound<SER: Serialize>(&mut self, kind: BoundKind, key: &SER) -> Option<Bound> {
+        let mut key_data = Vec::new();
+        let res = serialize_to_buf_mut(&mut key_data, key)
+            .and_then(|_| read_array_header(&key_data));
+        match res {
+            Ok(_) => Some(Bound { kind, key_data }),
+            Err(e) => {
+                self.error.get_or_insert(e);
+                None
+            }
+        }
+    }
+
+    /// rows with index key greater or equal to key
+    pub fn ge<SER: Serialize>(mut self, key: &SER) -> Self {
+        self.lower = self.make_bound(BoundKind::Included, key);
+        self
+    }
+
+    /// rows with index key greater than key
+    pub fn gt<SER: Serialize>(mut self, key: &SER) -> Self {
+        self.lower = self.make_bound(BoundKind::Excluded, key);
+        self
+    }
+
+    /// rows with index key lower or equal to key
+    pub fn le<SER: Serialize>(mut self, key: &SER) -> Self {
+        self.upper = self.make_bound(BoundKind::Included, key);
+        self
+    }
+
+    /// rows with index key lower than key
+    pub fn lt<SER: Serialize>(mut self, key: &SER) -> Self {
+        self.upper = self.make_bound(BoundKind::Excluded, key);
+        self
+    }
+
+    /// rows whose leading index key parts are equal to key, can't be combined with bounds
+    pub fn prefix<SER: Serialize>(mut self, key: &SER) -> Self {
+        self.prefix = self.make_bound(BoundKind::Included, key).map(|bound| bound.key_data);
+        self
+    }
+
+    /// iterate from upper bound to lower one
+    pub fn reverse(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn iter(self) -> io::Result<SelectIterator<'ctx>> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        if self.prefix.is_some() && (self.lower.is_some() || self.upper.is_some()) {
+            return Err(make_error("Select by prefix can't have bounds!".to_string(), false));
+        }
+        let (space_id, index_id) = self.ctx.get_space_and_index_id(&self.space_name, &self.index_name)?;
+
+        let (iterator_type, key_data, stop) = match (self.prefix, self.reverse) {
+            (Some(prefix), false) => (IteratorType::EQ, prefix, None),
+            (Some(prefix), true) => (IteratorType::REQ, prefix, None),
+            (None, false) => match self.lower {
+                Some(Bound { kind: BoundKind::Included, key_data, .. }) => (IteratorType::GE, key_data, self.upper),
+                Some(Bound { kind: BoundKind::Excluded, key_data, .. }) => (IteratorType::GT, key_data, self.upper),
+                None => (IteratorType::ALL, EMPTY_KEY.to_vec(), self.upper),
+            },
+            (None, true) => match self.upper {
+                Some(Bound { kind: BoundKind::Included, key_data, .. }) => (IteratorType::LE, key_data, self.lower),
+                Some(Bound { kind: BoundKind::Excluded, key_data, .. }) => (IteratorType::LT, key_data, self.lower),
+                None => (IteratorType::LE, EMPTY_KEY.to_vec(), self.lower),
+            },
+        };
+
+        let stop = match stop {
+            Some(bound) => {
+                let key_def = self.ctx.key_def(&self.space_name, &self.index_name)?;
+                Some(StopCondition { key_def, bound, descending: self.reverse })
+            }
+            None => None,
+        };
+
+        let inner = self.ctx.index_iterator_data(space_id, index_id, iterator_type, key_data)?;
+        Ok(SelectIterator {
+            inner,
+            stop,
+            skip: self.offset,
+            remaining: self.limit,
+            finished: false,
+        })
+    }
+
+    /// decode all selected rows as T
+    pub fn to_vec<T: DeserializeOwned>(self) -> io::Result<Vec<T>> {
+        self.iter()?.typed().collect()
+    }
+}
+
+#[derive(Debug)]
+struct StopCondition {
+    key_def: KeyDef,
+    bound: Bound,
+    descending: bool,
+}
+
+impl StopCondition {
+    fn reached(&self, row: &TarantoolTuple) -> io::Result<bool> {
+        let ordering = self.key_def.compare_with_key_data(row, &self.bound.key_data)?;
+        Ok(match (self.descending, self.bound.kind) {
+            (false, BoundKind::Included) => ordering == Ordering::Greater,
+            (false, BoundKind::Excluded) => ordering != Ordering::Less,
+            (true, BoundKind::Included) => ordering == Ordering::Less,
+            (true, BoundKind::Excluded) => ordering != Ordering::Greater,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct SelectIterator<'ctx> {
+    inner: TarantoolIterator<'ctx>,
+    stop: Option<StopCondition>,
+    skip: usize,
+    remaining: Option<usize>,
+    finished: bool,
+}
+
+impl<'ctx> Iterator for SelectIterator<'ctx> {
+    type Item = io::Result<TarantoolTuple<'ctx>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished || self.remaining == Some(0) {
+            return None;
+        }
+        loop {
+            let row = match self.inner.next() {
+                None => {
+                    self.finished = true;
+                    return None;
+                }
+                Some(Err(e)) => return Some(Err(e)),
+                Some(Ok(row)) => row,
+            };
+            if let Some(ref stop) = self.stop {
+                match stop.reached(&row) {
+                    Ok(true) => {
+                        self.finished = true;
+                        return None;
+                    }
+                    Ok(false) => {}
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            if self.skip > 0 {
+                self.skip -= 1;
+                continue;
+            }
+            if let Some(ref mut remaining) = self.remaining {
+                *remaining -= 1;
+            }
+            return Some(Ok(row));
+        }
+    }
+}
+
+impl TarantoolContext {
+    /// start range query over index, see Select
+    pub fn select<S, S1>(&self, space_name: S, index_name: S1) -> Select<'_>
+        where S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        Select::new(self,
+                    String::from_utf8_lossy(space_name.as_ref()).into_owned(),
+                    String::from_utf8_lossy(index_name.as_ref()).into_owned())
+    }
+}
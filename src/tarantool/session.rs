@@ -0,0 +1,263 @@
+use rmpv::Value;
+use std::collections::HashSet;
+use std::io;
+use std::mem;
+use std::os::raw::{c_char, c_int};
+use crate::tarantool::api::*;
+use crate::tarantool::internal::*;
+
+type SessionIdFn = unsafe extern "C" fn() -> u64;
+type SessionUserIdFn = unsafe extern "C" fn(*mut u32) -> c_int;
+type EffectiveUserIdFn = unsafe extern "C" fn() -> u32;
+type AccessCheckSpaceFn = unsafe extern "C" fn(u32, u16) -> c_int;
+
+unsafe fn server_fn(name: &[u8]) -> *mut libc::c_void {
+    libc::dlsym(libc::RTLD_DEFAULT, name.as_ptr() as *const c_char)
+}
+
+lazy_static! {
+    /// session C API is exported only by newer servers, lua of box.session is used otherwise
+    static ref SESSION_ID: Option<SessionIdFn> = unsafe {
+        let ptr = server_fn(b"box_session_id\0");
+        if ptr.is_null() { None } else { Some(mem::transmute::<*mut libc::c_void, SessionIdFn>(ptr)) }
+    };
+    static ref SESSION_USER_ID: Option<SessionUserIdFn> = unsafe {
+        let ptr = server_fn(b"box_session_user_id\0");
+        if ptr.is_null() { None } else { Some(mem::transmute::<*mut libc::c_void, SessionUserIdFn>(ptr)) }
+    };
+    static ref EFFECTIVE_USER_ID: Option<EffectiveUserIdFn> = unsafe {
+        let ptr = server_fn(b"box_effective_user_id\0");
+        if ptr.is_null() { None } else { Some(mem::transmute::<*mut libc::c_void, EffectiveUserIdFn>(ptr)) }
+    };
+    /// without it privileges are computed from _vpriv, it shows privileges granted to or by the user,
+    /// so privileges of roles are seen only if user can read whole _priv, see granted_privileges
+    static ref ACCESS_CHECK_SPACE: Option<AccessCheckSpaceFn> = unsafe {
+        let ptr = server_fn(b"box_access_check_space\0");
+        if ptr.is_null() { None } else { Some(mem::transmute::<*mut libc::c_void, AccessCheckSpaceFn>(ptr)) }
+    };
+}
+
+const ADMIN_USER_ID: u32 = 1;
+const PUBLIC_ROLE_ID: u32 = 2;
+const SUPER_ROLE_ID: u32 = 31;
+
+/// Privileges on space, values match enum box_privilege_type of tarantool
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Privilege {
+    Read = 1,
+    Write = 2,
+    Execute = 4,
+    Create = 32,
+    Drop = 64,
+    Alter = 128,
+}
+
+impl Privilege {
+    pub fn from_name(name: &str) -> Option<Privilege> {
+        match name {
+            "read" => Some(Privilege::Read),
+            "write" => Some(Privilege::Write),
+            "execute" => Some(Privilege::Execute),
+            "create" => Some(Privilege::Create),
+            "drop" => Some(Privilege::Drop),
+            "alter" => Some(Privilege::Alter),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Privilege::Read => "read",
+            Privilege::Write => "write",
+            Privilege::Execute => "execute",
+            Privilege::Create => "create",
+            Privilege::Drop => "drop",
+            Privilege::Alter => "alter",
+        }
+    }
+}
+
+/// Caller of the proc
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    pub id: u64,
+    /// user who authenticated the session
+    pub user_id: u32,
+    pub user_name: String,
+    /// user whose privileges are checked now, differs from session user in setuid functions and box.session.su
+    pub effective_user_id: u32,
+    pub effective_user_name: String,
+    /// address of remote client, None for local calls
+    pub peer: Option<String>,
+    /// sync of current request, 0 for local calls
+    pub sync: u64,
+}
+
+impl TarantoolContext {
+    fn eval_integer(&self, code: &str) -> io::Result<Option<i64>> {
+        let call = self.init_eval(code)?;
+        call.call()?;
+        call.pop_integer()
+    }
+
+    fn eval_str(&self, code: &str) -> io::Result<Option<String>> {
+        let call = self.init_eval(code)?;
+        call.call()?;
+        call.pop_str()
+    }
+
+    pub fn session_id(&self) -> io::Result<u64> {
+        match *SESSION_ID {
+            Some(session_id) => Ok(unsafe { session_id() }),
+            None => Ok(self.eval_integer("return box.session.id()")?.unwrap_or(0) as u64),
+        }
+    }
+
+    /// id of user who authenticated the session
+    pub fn session_user_id(&self) -> io::Result<u32> {
+        match *SESSION_USER_ID {
+            Some(session_user_id) => {
+                let mut uid: u32 = 0;
+                if unsafe { session_user_id(&mut uid) } != 0 {
+                    return make_error_result("error on get session user id!".to_string());
+                }
+                Ok(uid)
+            }
+            None => Ok(self.eval_integer("return box.session.uid()")?.unwrap_or(0) as u32),
+        }
+    }
+
+    /// id of user whose privileges are checked now
+    pub fn effective_user_id(&self) -> io::Result<u32> {
+        match *EFFECTIVE_USER_ID {
+            Some(effective_user_id) => Ok(unsafe { effective_user_id() }),
+            None => Ok(self.eval_integer("return box.session.euid()")?.unwrap_or(0) as u32),
+        }
+    }
+
+    /// name of user or role by id, taken from _vuser which every user can read for itself
+    pub fn user_name(&self, user_id: u32) -> io::Result<String> {
+        match self.index_get("_vuser", "primary", &(user_id, ))? {
+            Some(row) => row.decode_field(2),
+            None => Err(make_error(format!("Unknown user! user id={}", user_id), false)),
+        }
+    }
+
+    pub fn session_user_name(&self) -> io::Result<String> {
+        self.user_name(self.session_user_id()?)
+    }
+
+    pub fn effective_user_name(&self) -> io::Result<String> {
+        self.user_name(self.effective_user_id()?)
+    }
+
+    /// address of remote client, None for local calls
+    pub fn session_peer(&self) -> io::Result<Option<String>> {
+        self.eval_str("return box.session.peer()")
+    }
+
+    /// sync of current request, 0 for local calls
+    pub fn session_sync(&self) -> io::Result<u64> {
+        Ok(self.eval_integer("return box.session.sync()")?.unwrap_or(0) as u64)
+    }
+
+    pub fn session_info(&self) -> io::Result<SessionInfo> {
+        let user_id = self.session_user_id()?;
+        let effective_user_id = self.effective_user_id()?;
+        Ok(SessionInfo {
+            id: self.session_id()?,
+            user_id,
+            user_name: self.user_name(user_id)?,
+            effective_user_id,
+            effective_user_name: self.user_name(effective_user_id)?,
+            peer: self.session_peer()?,
+            sync: self.session_sync()?,
+        })
+    }
+
+    /// privileges of user on space: granted to user, its roles (recursively) and public role, on the space, all spaces or universe;
+    /// second value is false when privileges of roles may be hidden by _vpriv, user without universe read sees only
+    /// rows granted to or by itself
+    fn granted_privileges(&self, user_id: u32, space_id: u32) -> io::Result<(u32, bool)> {
+        if user_id == ADMIN_USER_ID {
+            return Ok((u32::MAX, true));
+        }
+        let owner: Option<u32> = match self.index_get("_vspace", "primary", &(space_id, ))? {
+            Some(row) => row.decode_field(1)?,
+            None => return Err(make_error(format!("Unknown space! space id={}", space_id), false)),
+        };
+        if owner == Some(user_id) {
+            return Ok((u32::MAX, true));
+        }
+        let mut mask = 0;
+        let mut universe = 0;
+        let mut grantees = vec![user_id, PUBLIC_ROLE_ID];
+        let mut seen: HashSet<u32> = grantees.iter().cloned().collect();
+        while let Some(grantee) = grantees.pop() {
+            for row in self.index_iterator("_vpriv", "primary", IteratorType::EQ, &(grantee, ))?.typed::<(u32, u32, String, Value, u32)>() {
+                let (_grantor, _grantee, object_type, object_id, privilege) = row?;
+                let object_id = object_id.as_u64();
+                match object_type.as_str() {
+                    "universe" => {
+                        mask |= privilege;
+                        universe |= privilege;
+                    }
+                    //empty object id grants privilege on all spaces
+                    "space" if object_id.is_none() || object_id == Some(space_id as u64) => mask |= privilege,
+                    "role" if privilege & Privilege::Execute as u32 != 0 => {
+                        if object_id == Some(SUPER_ROLE_ID as u64) {
+                            return Ok((u32::MAX, true));
+                        }
+                        if let Some(role_id) = object_id {
+                            if seen.insert(role_id as u32) {
+                                grantees.push(role_id as u32);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok((mask, universe & Privilege::Read as u32 != 0))
+    }
+
+    /// true when effective user has privilege on space;
+    /// on servers without box_access_check_space error is returned instead of false when privileges of user roles can't be read
+    pub fn has_privilege<S>(&self, space_name: S, privilege: &str) -> io::Result<bool>
+        where S: AsRef<[u8]>
+    {
+        let privilege = Privilege::from_name(privilege)
+            .ok_or_else(|| make_error(format!("Unknown privilege {:?}!", privilege), false))?;
+        let space_id = self.get_space_id(&space_name)?;
+        match *ACCESS_CHECK_SPACE {
+            Some(access_check_space) => Ok(unsafe { access_check_space(space_id, privilege as u16) } == 0),
+            None => match self.granted_privileges(self.effective_user_id()?, space_id)? {
+                (mask, _) if mask & privilege as u32 != 0 => Ok(true),
+                (_, true) => Ok(false),
+                (_, false) => Err(make_error(format!("Can't check {} access to space '{}', privileges of roles are not visible to user!",
+                                                     privilege.name(), String::from_utf8_lossy(space_name.as_ref())), false)),
+            },
+        }
+    }
+
+    /// fail with PermissionDenied unless effective user has privilege (read, write, create, drop, alter) on space,
+    /// other error when privilege can't be checked, see has_privilege
+    ///
+    /// # Examples
+    ///
+    /// tarantool.check_privilege(ORDERS, "write")?;
+    /// if order.owner != tarantool.session_user_name()? {
+    ///     tarantool.check_privilege(ALL_ORDERS, "write")?;
+    /// }
+    ///
+    pub fn check_privilege<S>(&self, space_name: S, privilege: &str) -> io::Result<()>
+        where S: AsRef<[u8]>
+    {
+        if self.has_privilege(&space_name, privilege)? {
+            return Ok(());
+        }
+        Err(io::Error::new(io::ErrorKind::PermissionDenied,
+                           format!("{} access to space '{}' is denied for user '{}'",
+                                   privilege, String::from_utf8_lossy(space_name.as_ref()), self.effective_user_name()?)))
+    }
+}
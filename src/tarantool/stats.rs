@@ -0,0 +1,209 @@
+use rmpv::Value;
+use serde::Serialize;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::ptr;
+use std::str::from_utf8_unchecked;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::tarantool::api::*;
+use crate::tarantool::internal::*;
+
+thread_local! {
+    static RANDOM_STATE: Cell<u64> = const { Cell::new(0) };
+}
+
+/// xorshift64*, seeded by clock at first use, good enough to pick sample tuples
+fn next_random() -> u32 {
+    RANDOM_STATE.with(|state| {
+        let mut x = state.get();
+        if x == 0 {
+            x = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0) | 1;
+        }
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        state.set(x);
+        (x.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 32) as u32
+    })
+}
+
+/// Cardinality and distribution of key estimated by random sample of index tuples
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyStats {
+    /// tuples in index
+    pub rows: usize,
+    /// tuples in sample
+    pub sample_size: usize,
+    /// distinct keys in sample
+    pub sample_distinct: usize,
+    /// estimated distinct keys in index, exact when sample is the whole index
+    pub distinct: usize,
+    /// keys of sample with number of tuples in sample, most frequent first
+    pub frequencies: Vec<(Vec<Value>, usize)>,
+}
+
+impl KeyStats {
+    /// estimated average number of tuples with the same key
+    pub fn rows_per_key(&self) -> f64 {
+        if self.distinct == 0 {
+            return 0.0;
+        }
+        self.rows as f64 / self.distinct as f64
+    }
+
+    /// estimated share of tuples with key, 0 for keys absent in sample
+    pub fn fraction<K: Serialize>(&self, key: &K) -> io::Result<f64> {
+        if self.sample_size == 0 {
+            return Ok(0.0);
+        }
+        let mut key_data = Vec::new();
+        serialize_to_vec(&mut key_data, key)?;
+        let key: Vec<Value> = decode_serde(&key_data[..])?;
+        Ok(self.frequencies.iter()
+            .find(|(value, _)| *value == key)
+            .map_or(0.0, |(_, count)| *count as f64 / self.sample_size as f64))
+    }
+}
+
+/// guaranteed error estimator of distinct values: sqrt(rows / sample) * singletons + values seen more than once
+fn estimate_distinct(rows: usize, sample_size: usize, counts: &HashMap<Vec<u8>, usize>) -> usize {
+    if sample_size == 0 || sample_size >= rows {
+        return counts.len();
+    }
+    let singletons = counts.values().filter(|count| **count == 1).count();
+    let repeated = counts.len() - singletons;
+    let estimate = (rows as f64 / sample_size as f64).sqrt() * singletons as f64 + repeated as f64;
+    (estimate.round() as usize).clamp(counts.len(), rows)
+}
+
+impl TarantoolContext {
+    /// number of tuples in index
+    pub fn index_len<S, S1>(&self, space_name: S, index_name: S1) -> io::Result<usize>
+        where S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        let (space_id, index_id) = self.get_space_and_index_id(&space_name, &index_name)?;
+        let res = unsafe { box_index_len(space_id, index_id) };
+        if res < 0 {
+            return make_error_result(format!("error on index len! space name={} index name={} ",
+                                             unsafe { from_utf8_unchecked(space_name.as_ref()) }, unsafe { from_utf8_unchecked(index_name.as_ref()) }));
+        }
+        Ok(res as usize)
+    }
+
+    /// memory used by index in bytes
+    pub fn index_bsize<S, S1>(&self, space_name: S, index_name: S1) -> io::Result<usize>
+        where S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        let (space_id, index_id) = self.get_space_and_index_id(&space_name, &index_name)?;
+        let res = unsafe { box_index_bsize(space_id, index_id) };
+        if res < 0 {
+            return make_error_result(format!("error on index bsize! space name={} index name={} ",
+                                             unsafe { from_utf8_unchecked(space_name.as_ref()) }, unsafe { from_utf8_unchecked(index_name.as_ref()) }));
+        }
+        Ok(res as usize)
+    }
+
+    /// tuple of index picked by rnd, None for empty index; supported by memtx indexes
+    pub fn index_random<S, S1>(&self, space_name: S, index_name: S1, rnd: u32) -> io::Result<Option<TarantoolTuple<'_>>>
+        where S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        let (space_id, index_id) = self.get_space_and_index_id(&space_name, &index_name)?;
+        self.index_random_int(space_id, index_id, rnd)
+    }
+
+    pub fn index_random_int(&self, space_id: u32, index_id: u32, rnd: u32) -> io::Result<Option<TarantoolTuple<'_>>> {
+        let mut res_tuple: *mut u8 = ptr::null_mut();
+        let res = unsafe { box_index_random(space_id, index_id, rnd, &mut res_tuple) };
+        if res == -1 {
+            return make_error_result(format!("error on index random! space id={} index id={} ", space_id, index_id));
+        }
+        if res_tuple.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(TarantoolTuple::from_ptr(res_tuple).with_space_id(space_id)))
+    }
+
+    fn index_sample_int(&self, space_id: u32, index_id: u32, len: usize, size: usize) -> io::Result<Vec<OwnedTuple>> {
+        if len <= size {
+            return self.index_iterator_data(space_id, index_id, IteratorType::ALL, vec![0x90])?
+                .map(|row| row.map(|row| row.to_owned_tuple()))
+                .collect();
+        }
+        let mut seen = HashSet::new();
+        let mut sample = Vec::with_capacity(size);
+        //tuples are picked again when already taken, attempts are limited for tiny tails of huge indexes
+        for _ in 0..size * 4 + 16 {
+            if sample.len() == size {
+                break;
+            }
+            if let Some(row) = self.index_random_int(space_id, index_id, next_random())? {
+                if seen.insert(row.row_data as usize) {
+                    sample.push(row.to_owned_tuple());
+                }
+            }
+        }
+        Ok(sample)
+    }
+
+    /// up to size distinct random tuples of index without full scan, whole index if it is not bigger than size
+    ///
+    /// # Examples
+    ///
+    /// for row in tarantool.index_sample(TEST_SPACE, PRIMARY_INDEX, 100)? {
+    ///     let row: RowTypeStruct = row.as_tuple().decode()?;
+    /// }
+    ///
+    pub fn index_sample<S, S1>(&self, space_name: S, index_name: S1, size: usize) -> io::Result<Vec<OwnedTuple>>
+        where S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        let (space_id, index_id) = self.get_space_and_index_id(&space_name, &index_name)?;
+        let len = self.index_len(&space_name, &index_name)?;
+        self.index_sample_int(space_id, index_id, len, size)
+    }
+
+    /// estimate cardinality and distribution of index key by sample of tuples
+    ///
+    /// # Examples
+    ///
+    /// let stats = tarantool.key_stats(TEST_SPACE, SECONDARY_INDEX, 1000)?;
+    /// if stats.rows_per_key() > 100.0 {
+    ///     //key is not selective, scan other index
+    /// }
+    ///
+    pub fn key_stats<S, S1>(&self, space_name: S, index_name: S1, sample_size: usize) -> io::Result<KeyStats>
+        where S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        let (space_id, index_id) = self.get_space_and_index_id(&space_name, &index_name)?;
+        let key_fields = self.get_index_key_fields(space_id, index_id)?;
+        self.field_stats(space_name, index_name, &key_fields, sample_size)
+    }
+
+    /// estimate cardinality and distribution of listed fields by sample of index tuples
+    pub fn field_stats<S, S1>(&self, space_name: S, index_name: S1, fields: &[u32], sample_size: usize) -> io::Result<KeyStats>
+        where S: AsRef<[u8]>,
+              S1: AsRef<[u8]>
+    {
+        let (space_id, index_id) = self.get_space_and_index_id(&space_name, &index_name)?;
+        let rows = self.index_len(&space_name, &index_name)?;
+        let sample = self.index_sample_int(space_id, index_id, rows, sample_size)?;
+
+        let mut counts: HashMap<Vec<u8>, usize> = HashMap::new();
+        let mut key = Vec::new();
+        for row in sample.iter() {
+            row.as_tuple().copy_fields_to_buf(fields, &mut key)?;
+            *counts.entry(key.clone()).or_insert(0) += 1;
+        }
+        let distinct = estimate_distinct(rows, sample.len(), &counts);
+        let mut frequencies = counts.iter()
+            .map(|(key, count)| Ok((decode_serde(&key[..])?, *count)))
+            .collect::<io::Result<Vec<(Vec<Value>, usize)>>>()?;
+        frequencies.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| compare_keys(&a.0, &b.0)));
+        Ok(KeyStats { rows, sample_size: sample.len(), sample_distinct: counts.len(), distinct, frequencies })
+    }
+}
@@ -0,0 +1,221 @@
+use rmp_serde::decode::Error as MsgpackError;
+use rmp_serde::Deserializer as MsgpackDeserializer;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Deserializer, SeqAccess, Visitor};
+use std::cell::Cell;
+use std::error;
+use std::fmt;
+use std::io;
+use crate::tarantool::api::*;
+use crate::tarantool::ext::ext_to_bins;
+use crate::tarantool::msgpack::*;
+
+/// Field of row which can't be decoded, returned inside io::Error by tolerant decoding
+///
+/// # Examples
+///
+/// if let Some(e) = err.get_ref().and_then(|e| e.downcast_ref::<FieldDecodeError>()) {
+///     println!("field {} is broken", e.field);
+/// }
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDecodeError {
+    /// zero based field number in tuple
+    pub field: u32,
+    /// struct field name, None for tuples
+    pub name: Option<&'static str>,
+    /// decoder message with expected type
+    pub message: String,
+}
+
+impl fmt::Display for FieldDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name {
+            Some(name) => write!(f, "can't decode field {} ({}): {}", self.field, name, self.message),
+            None => write!(f, "can't decode field {}: {}", self.field, self.message),
+        }
+    }
+}
+
+impl error::Error for FieldDecodeError {}
+
+#[derive(Debug)]
+enum TolerantError {
+    /// field is absent in tuple and its type is not Option
+    Missing,
+    Field(FieldDecodeError),
+    Custom(String),
+}
+
+impl fmt::Display for TolerantError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TolerantError::Missing => write!(f, "field is missing"),
+            TolerantError::Field(e) => e.fmt(f),
+            TolerantError::Custom(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl error::Error for TolerantError {}
+
+impl de::Error for TolerantError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        TolerantError::Custom(msg.to_string())
+    }
+}
+
+/// message of rmp_serde error with details, its Display gives only error kind
+pub(crate) fn decode_error_message(e: &MsgpackError) -> String {
+    match e {
+        MsgpackError::Syntax(message) | MsgpackError::Uncategorized(message) => message.clone(),
+        MsgpackError::TypeMismatch(marker) => format!("unexpected msgpack {:?}", marker),
+        MsgpackError::LengthMismatch(len) => format!("unexpected array length {}", len),
+        e => format!("{:?}", e),
+    }
+}
+
+/// stands for field absent in tuple, Option fields become None, others are reported as missing
+struct MissingField;
+
+impl<'de> Deserializer<'de> for MissingField {
+    type Error = TolerantError;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        Err(TolerantError::Missing)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        visitor.visit_none()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// fields of row given one by one to derived visitor, the row may be shorter or longer than the struct
+struct TolerantFields<'a> {
+    data: &'a [u8],
+    pos: usize,
+    count: u32,
+    index: u32,
+    names: Option<&'static [&'static str]>,
+    /// first field which is absent in tuple and has no default
+    missing: &'a Cell<Option<u32>>,
+}
+
+impl<'a> TolerantFields<'a> {
+    fn name(&self, index: u32) -> Option<&'static str> {
+        self.names.and_then(|names| names.get(index as usize).cloned())
+    }
+
+    fn field_error(&self, message: String) -> TolerantError {
+        TolerantError::Field(FieldDecodeError { field: self.index, name: self.name(self.index), message })
+    }
+}
+
+impl<'de, 'a> SeqAccess<'de> for TolerantFields<'a> {
+    type Error = TolerantError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> where T: DeserializeSeed<'de> {
+        if self.index >= self.count {
+            let res = match seed.deserialize(MissingField) {
+                Ok(value) => Some(value),
+                Err(TolerantError::Missing) => {
+                    //derived visitor uses default for #[serde(default)] fields or fails with invalid length
+                    if self.missing.get().is_none() {
+                        self.missing.set(Some(self.index));
+                    }
+                    None
+                }
+                Err(e) => return Err(e),
+            };
+            self.index += 1;
+            return Ok(res);
+        }
+        let len = value_len(&self.data[self.pos..]).map_err(|e| self.field_error(e.to_string()))?;
+        let field = &self.data[self.pos..self.pos + len];
+        let value = seed.deserialize(&mut MsgpackDeserializer::new(field)).map_err(|e| self.field_error(decode_error_message(&e)))?;
+        self.pos += len;
+        self.index += 1;
+        Ok(Some(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.names.map(|names| names.len())
+    }
+}
+
+/// whole row, only structs and tuples are decoded tolerantly
+struct TolerantRow<'a> {
+    data: &'a [u8],
+    missing: &'a Cell<Option<u32>>,
+}
+
+impl<'a> TolerantRow<'a> {
+    fn fields(&self, names: Option<&'static [&'static str]>) -> Result<TolerantFields<'a>, TolerantError> {
+        let (count, pos) = read_array_header(self.data).map_err(|e| TolerantError::Custom(e.to_string()))?;
+        Ok(TolerantFields { data: self.data, pos, count, index: 0, names, missing: self.missing })
+    }
+}
+
+impl<'de, 'a> Deserializer<'de> for TolerantRow<'a> {
+    type Error = TolerantError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        (&mut MsgpackDeserializer::new(self.data)).deserialize_any(visitor).map_err(|e| TolerantError::Custom(decode_error_message(&e)))
+    }
+
+    fn deserialize_struct<V>(self, _name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        visitor.visit_seq(self.fields(Some(fields))?)
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        visitor.visit_seq(self.fields(None)?)
+    }
+
+    fn deserialize_tuple_struct<V>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        visitor.visit_seq(self.fields(None)?)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq
+        map enum identifier ignored_any
+    }
+}
+
+/// decode msgpack row as T for rolling schema upgrades:
+/// fields missing at the end of row are None (or default with #[serde(default)]), extra trailing fields are ignored,
+/// mismatch of a field is reported as FieldDecodeError with field number and expected type
+pub fn decode_tolerant<T>(data: &[u8]) -> io::Result<T> where T: DeserializeOwned {
+    let converted = ext_to_bins(data)?;
+    let data = converted.as_deref().unwrap_or(data);
+    let missing = Cell::new(None);
+    T::deserialize(TolerantRow { data, missing: &missing }).map_err(|e| match (e, missing.get()) {
+        (TolerantError::Field(e), _) => io::Error::new(io::ErrorKind::InvalidData, e),
+        (e, Some(field)) => {
+            let name = struct_field_names::<T>().and_then(|names| names.get(field as usize).cloned());
+            io::Error::new(io::ErrorKind::InvalidData, FieldDecodeError { field, name, message: format!("field is missing in tuple, {}", e) })
+        }
+        (e, None) => io::Error::new(io::ErrorKind::InvalidData, e.to_string()),
+    })
+}
+
+impl<'ctx> TarantoolTuple<'ctx> {
+    /// decode row written by older or newer version of T, see decode_tolerant
+    ///
+    /// # Examples
+    ///
+    /// let user: UserV2 = row.decode_tolerant()?;
+    ///
+    pub fn decode_tolerant<T>(&self) -> io::Result<T> where T: DeserializeOwned {
+        decode_tolerant(self.get_data())
+    }
+}
@@ -0,0 +1,228 @@
+use serde::ser::{Serialize, Serializer};
+use std::io;
+use crate::tarantool::api::*;
+use crate::tarantool::ext::{ext_writer_active, serialize_raw_msgpack};
+use crate::tarantool::internal::*;
+use crate::tarantool::msgpack::*;
+
+/// Tuple made in memory, it is referenced while the value is alive
+///
+/// tuples returned by box are kept by tarantool only until the next box call, keep OwnedTuple to use them longer
+#[derive(Debug)]
+pub struct OwnedTuple {
+    ptr: *const u8,
+}
+
+impl OwnedTuple {
+    /// take reference to tuple, ptr must be valid box_tuple_t
+    pub(crate) fn from_ptr(ptr: *const u8) -> OwnedTuple {
+        unsafe {
+            box_tuple_ref(ptr);
+        }
+        OwnedTuple { ptr }
+    }
+
+    /// make tuple of msgpack array with default format
+    pub fn from_data(data: &[u8]) -> io::Result<OwnedTuple> {
+        read_array_header(data)?;
+        let data = data.as_ptr_range();
+        let ptr = unsafe { box_tuple_new(box_tuple_format_default(), data.start, data.end) };
+        if ptr.is_null() {
+            return make_error_result("error on create tuple!".to_string());
+        }
+        Ok(OwnedTuple::from_ptr(ptr))
+    }
+
+    pub fn as_tuple(&self) -> TarantoolTuple<'_> {
+        TarantoolTuple::from_ptr(self.ptr)
+    }
+
+    pub fn as_ptr(&self) -> *const u8 {
+        self.ptr
+    }
+}
+
+impl Clone for OwnedTuple {
+    fn clone(&self) -> OwnedTuple {
+        OwnedTuple::from_ptr(self.ptr)
+    }
+}
+
+impl Drop for OwnedTuple {
+    fn drop(&mut self) {
+        unsafe {
+            box_tuple_unref(self.ptr);
+        }
+    }
+}
+
+/// written as msgpack array of its fields, ext values are kept
+/// written as is by msgpack serialization of the crate, other serializers get decoded fields
+impl Serialize for OwnedTuple {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let tuple = self.as_tuple();
+        if ext_writer_active() {
+            return serialize_raw_msgpack(serializer, tuple.get_data());
+        }
+        TupleValue::from_data(tuple.get_data())
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+/// Builder of tuple fields written straight to msgpack, errors of serialization are returned by build
+///
+/// # Examples
+///
+/// let tuple = TupleBuilder::new()
+///     .field(&id)
+///     .field(&name)
+///     .nil()
+///     .field(&Decimal::from(10))
+///     .build()?;
+/// call.push_owned_tuple(&tuple);
+///
+#[derive(Debug, Default)]
+pub struct TupleBuilder {
+    fields: Vec<u8>,
+    count: u32,
+    error: Option<io::Error>,
+}
+
+impl TupleBuilder {
+    pub fn new() -> TupleBuilder {
+        TupleBuilder::default()
+    }
+
+    /// append value as one field, structs and tuples become nested arrays
+    pub fn field<SER: Serialize>(mut self, value: &SER) -> Self {
+        if self.error.is_none() {
+            match serialize_to_vec(&mut self.fields, value) {
+                Ok(_) => self.count += 1,
+                Err(e) => self.error = Some(e),
+            }
+        }
+        self
+    }
+
+    pub fn nil(mut self) -> Self {
+        self.fields.push(MSGPACK_NIL);
+        self.count += 1;
+        self
+    }
+
+    /// append already encoded msgpack value, data must hold exactly one value
+    pub fn raw(mut self, data: &[u8]) -> Self {
+        match value_len(data) {
+            Ok(len) if len == data.len() => {
+                self.fields.extend_from_slice(data);
+                self.count += 1;
+            }
+            Ok(len) => {
+                self.error.get_or_insert(make_error(format!("Raw field must be one msgpack value, {} bytes left!", data.len() - len), false));
+            }
+            Err(e) => {
+                self.error.get_or_insert(e);
+            }
+        }
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.count as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// msgpack array of appended fields
+    pub fn to_data(self) -> io::Result<Vec<u8>> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        let mut data = Vec::with_capacity(array_header_len(self.count) + self.fields.len());
+        write_array_header(&mut data, self.count);
+        data.extend_from_slice(&self.fields);
+        Ok(data)
+    }
+
+    pub fn build(self) -> io::Result<OwnedTuple> {
+        OwnedTuple::from_data(&self.to_data()?)
+    }
+}
+
+/// copy update ops changing zero based field numbers to one based, used by box_tuple_update and box_tuple_upsert
+fn ops_to_one_based(ops: &[u8]) -> io::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(ops.len());
+    let (count, mut pos) = read_array_header(ops)?;
+    out.extend_from_slice(&ops[..pos]);
+    for _ in 0..count {
+        let (op_len, header_len) = read_array_header(&ops[pos..])?;
+        out.extend_from_slice(&ops[pos..pos + header_len]);
+        pos += header_len;
+        for i in 0..op_len {
+            let len = value_len(&ops[pos..])?;
+            match read_int(&ops[pos..]) {
+                //negative numbers count from the end and don't depend on base
+                Ok((field_no, _)) if i == 1 && field_no >= 0 => {
+                    let _ = rmp::encode::write_sint(&mut out, field_no + 1);
+                }
+                _ => out.extend_from_slice(&ops[pos..pos + len]),
+            }
+            pos += len;
+        }
+    }
+    Ok(out)
+}
+
+type TupleUpdateFn = unsafe extern "C" fn(*const u8, *const u8, *const u8) -> *const u8;
+
+impl<'ctx> TarantoolTuple<'ctx> {
+    fn apply_ops<OPS: Serialize>(&self, ops: &OPS, index_base: IndexBase, f: TupleUpdateFn, name: &str) -> io::Result<OwnedTuple> {
+        let mut data = Vec::new();
+        serialize_to_vec(&mut data, ops)?;
+        if let IndexBase::Zero = index_base {
+            data = ops_to_one_based(&data)?;
+        }
+        let range = data.as_ptr_range();
+        let ptr = unsafe { f(self.row_data, range.start, range.end) };
+        if ptr.is_null() {
+            return make_error_result(format!("error on tuple {}!", name));
+        }
+        Ok(OwnedTuple::from_ptr(ptr))
+    }
+
+    /// apply update ops to copy of the tuple, space is not touched
+    ///
+    /// # Examples
+    ///
+    /// let updated = row.update(&(("+", 2, 1), ("=", 3, "done")), IndexBase::Zero)?;
+    /// if updated.as_tuple().decode_field::<u32>(2)? < limit {
+    ///     tarantool.replace(TEST_SPACE, &updated)?;
+    /// }
+    ///
+    pub fn update<OPS: Serialize>(&self, ops: &OPS, index_base: IndexBase) -> io::Result<OwnedTuple> {
+        self.apply_ops(ops, index_base, box_tuple_update, "update")
+    }
+
+    /// apply upsert ops to copy of the tuple, failed ops are skipped like in space upsert
+    pub fn upsert<OPS: Serialize>(&self, ops: &OPS, index_base: IndexBase) -> io::Result<OwnedTuple> {
+        self.apply_ops(ops, index_base, box_tuple_upsert, "upsert")
+    }
+
+    /// keep the tuple after next box call
+    pub fn to_owned_tuple(&self) -> OwnedTuple {
+        OwnedTuple::from_ptr(self.row_data)
+    }
+}
+
+impl<'a> LuaCall<'a> {
+    /// push tuple without serialization
+    pub fn push_owned_tuple(&mut self, tuple: &OwnedTuple) {
+        unsafe {
+            self.increment_param_count();
+            luaT_pushtuple(self.lua_state, tuple.as_ptr());
+        }
+    }
+}
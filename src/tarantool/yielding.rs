@@ -0,0 +1,153 @@
+use std::cmp::Ordering;
+use std::io;
+use std::ptr;
+use std::time::Duration;
+use crate::tarantool::api::*;
+use crate::tarantool::cursor::{is_reverse, resume_after, ResumeFilter, ScanKeys};
+use crate::tarantool::internal::*;
+use crate::tarantool::keydef::KeyDef;
+
+/// When TarantoolIterator gives control to other fibers during long scan
+///
+/// # Examples
+///
+/// for row in tarantool.index_iterator_all(COUNTRY_SPACE, COUNTRY_INDEX)?.with_yield_policy(YieldPolicy::EveryRows(1000))? {
+///     ...
+/// }
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum YieldPolicy {
+    Never,
+    /// yield after every N returned rows
+    EveryRows(usize),
+    /// yield when tx thread spent N microseconds of CPU time since previous yield,
+    /// work of other fibers counts too if loop body yields by itself
+    EveryMicros(u64),
+}
+
+fn thread_cpu_time() -> Duration {
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    unsafe {
+        libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts);
+    }
+    Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+}
+
+/// Yield bookkeeping of one iterator, keeps last returned tuple referenced to restore position after yield
+#[derive(Debug)]
+pub(crate) struct YieldState {
+    policy: YieldPolicy,
+    rows: usize,
+    started: Duration,
+    /// None for iterator which can't be repositioned
+    keys: Option<ScanKeys>,
+    last_tuple: *const u8,
+    schema_version: u64,
+    /// key of EQ/REQ iterator, checked by hand after iterator is repositioned
+    eq_key: Option<Vec<u8>>,
+    eq_key_def: Option<KeyDef>,
+    /// skips rows with key equal to last returned one after iterator is repositioned with GE/LE
+    resume: Option<ResumeFilter>,
+}
+
+impl YieldState {
+    pub(crate) fn new(policy: YieldPolicy, keys: Option<ScanKeys>, eq_key: Option<Vec<u8>>) -> YieldState {
+        YieldState {
+            policy,
+            rows: 0,
+            started: thread_cpu_time(),
+            keys,
+            last_tuple: ptr::null(),
+            schema_version: unsafe { box_schema_version() },
+            eq_key,
+            eq_key_def: None,
+            resume: None,
+        }
+    }
+
+    pub(crate) fn should_yield(&self) -> bool {
+        match self.policy {
+            YieldPolicy::Never => false,
+            YieldPolicy::EveryRows(rows) => self.rows >= rows,
+            YieldPolicy::EveryMicros(micros) => thread_cpu_time().saturating_sub(self.started) >= Duration::from_micros(micros),
+        }
+    }
+
+    pub(crate) fn row_returned(&mut self, tuple: *const u8) {
+        unsafe {
+            box_tuple_ref(tuple);
+            if !self.last_tuple.is_null() {
+                box_tuple_unref(self.last_tuple);
+            }
+        }
+        self.last_tuple = tuple;
+        self.rows += 1;
+    }
+
+    /// true once after schema was changed, server ends iterator silently then
+    pub(crate) fn schema_changed(&mut self) -> bool {
+        let schema_version = unsafe { box_schema_version() };
+        if schema_version == self.schema_version {
+            return false;
+        }
+        self.schema_version = schema_version;
+        true
+    }
+
+    pub(crate) fn can_reposition(&self) -> bool {
+        self.keys.is_some()
+    }
+
+    /// iterator type and key to continue scan after last returned row, None if there is no such row
+    ///
+    /// rows which go before it and rows which don't match EQ/REQ key are checked by hand from now
+    pub(crate) fn reposition(&mut self, ctx: &TarantoolContext, space_id: u32, index_id: u32, iterator_type: IteratorType) -> io::Result<Option<(IteratorType, Vec<u8>)>> {
+        let keys = match self.keys {
+            Some(ref keys) if !self.last_tuple.is_null() => keys,
+            _ => return Ok(None),
+        };
+        let mut last_key = Vec::new();
+        let mut last_primary_key = None;
+        keys.copy_keys(&TarantoolTuple::from_ptr(self.last_tuple), &mut last_key, &mut last_primary_key)?;
+        let (resume_type, resume) = resume_after(ctx, space_id, index_id, is_reverse(iterator_type), &last_key, last_primary_key.as_deref())?;
+        if self.eq_key.is_some() && self.eq_key_def.is_none() {
+            self.eq_key_def = Some(KeyDef::new(&ctx.index_info_by_id(space_id, index_id)?.parts)?);
+        }
+        self.resume = resume;
+        Ok(Some((resume_type, last_key)))
+    }
+
+    /// false for row which was returned before iterator was repositioned
+    pub(crate) fn passed_last_row(&mut self, row: &TarantoolTuple) -> io::Result<bool> {
+        let passed = match self.resume {
+            Some(ref resume) => resume.passed(row)?,
+            None => return Ok(true),
+        };
+        if passed {
+            self.resume = None;
+        }
+        Ok(passed)
+    }
+
+    pub(crate) fn eq_key_matches(&self, row: &TarantoolTuple) -> io::Result<bool> {
+        match (&self.eq_key, &self.eq_key_def) {
+            (Some(eq_key), Some(key_def)) => Ok(key_def.compare_with_key_data(row, eq_key)? == Ordering::Equal),
+            _ => Ok(true),
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.rows = 0;
+        self.started = thread_cpu_time();
+    }
+}
+
+impl Drop for YieldState {
+    fn drop(&mut self) {
+        if !self.last_tuple.is_null() {
+            unsafe {
+                box_tuple_unref(self.last_tuple);
+            }
+        }
+    }
+}